@@ -4,11 +4,20 @@ extern crate tiny_secp256k1;
 extern crate test;
 extern crate secp256k1;
 
-use tiny_secp256k1::{ECPointG, create_public_key};
+use tiny_secp256k1::{ECPointG, Window, Window2, Window8, ECPoint, ECJPoint, Scalar, MultiMulScratch, create_public_key, sign, verify, recover};
+use tiny_secp256k1::multi_mul::{strauss, pippenger};
+use tiny_secp256k1::ec_point::G;
 use secp256k1::{Secp256k1, key};
 
+use std::mem::MaybeUninit;
 use test::Bencher;
 
+const SECRET: [u8; 32] = [
+    0x32, 0x79, 0xe8, 0x0c, 0xb3, 0x93, 0x5c, 0x68, 0xdc, 0xf3, 0x71, 0xb9,
+    0xee, 0x21, 0x78, 0x73, 0x84, 0xba, 0xee, 0x63, 0xd6, 0x49, 0x0b, 0x17,
+    0x39, 0x27, 0x10, 0xc8, 0x76, 0xb1, 0xa8, 0x6b
+];
+
 #[bench]
 fn precalculate_ecpoint_g(b: &mut Bencher) {
     b.iter(|| {
@@ -16,14 +25,51 @@ fn precalculate_ecpoint_g(b: &mut Bencher) {
     });
 }
 
+#[bench]
+fn precalculate_ecpoint_g_window2(b: &mut Bencher) {
+    b.iter(|| {
+        ECPointG::with_window(Window2)
+    });
+}
+
+#[bench]
+fn precalculate_ecpoint_g_window8(b: &mut Bencher) {
+    let mut slot = Box::new(MaybeUninit::<ECPointG<Window8>>::uninit());
+
+    b.iter(|| {
+        ECPointG::init(&mut slot);
+    });
+}
+
+fn mul_generator<W: Window>(b: &mut Bencher, g: &ECPointG<W>) {
+    let k = Scalar::from_bytes(&SECRET).unwrap();
+
+    b.iter(|| {
+        g.mul(&k)
+    });
+}
+
+#[bench]
+fn mul_generator_window2(b: &mut Bencher) {
+    mul_generator(b, &ECPointG::with_window(Window2));
+}
+
+#[bench]
+fn mul_generator_window4(b: &mut Bencher) {
+    mul_generator(b, &ECPointG::new());
+}
+
+#[bench]
+fn mul_generator_window8(b: &mut Bencher) {
+    let mut slot = Box::new(MaybeUninit::<ECPointG<Window8>>::uninit());
+
+    mul_generator(b, ECPointG::init(&mut slot));
+}
+
 #[bench]
 fn secret_to_public(b: &mut Bencher) {
     let g = ECPointG::new();
-    let secret: &[u8] = &[
-        0x32, 0x79, 0xe8, 0x0c, 0xb3, 0x93, 0x5c, 0x68, 0xdc, 0xf3, 0x71, 0xb9,
-        0xee, 0x21, 0x78, 0x73, 0x84, 0xba, 0xee, 0x63, 0xd6, 0x49, 0x0b, 0x17,
-        0x39, 0x27, 0x10, 0xc8, 0x76, 0xb1, 0xa8, 0x6b
-    ];
+    let secret: &[u8] = &SECRET;
 
     b.iter(|| {
         create_public_key(&g, secret)
@@ -33,14 +79,100 @@ fn secret_to_public(b: &mut Bencher) {
 #[bench]
 fn secret_to_public_other(b: &mut Bencher) {
     let ctx = Secp256k1::new();
-    let secret: &[u8] = &[
-        0x32, 0x79, 0xe8, 0x0c, 0xb3, 0x93, 0x5c, 0x68, 0xdc, 0xf3, 0x71, 0xb9,
-        0xee, 0x21, 0x78, 0x73, 0x84, 0xba, 0xee, 0x63, 0xd6, 0x49, 0x0b, 0x17,
-        0x39, 0x27, 0x10, 0xc8, 0x76, 0xb1, 0xa8, 0x6b
-    ];
+    let secret: &[u8] = &SECRET;
 
     b.iter(|| {
         let s = key::SecretKey::from_slice(&ctx, secret).unwrap();
         key::PublicKey::from_secret_key(&ctx, &s).unwrap();
     })
 }
+
+#[bench]
+fn sign_message(b: &mut Bencher) {
+    let g = ECPointG::new();
+    let secret: &[u8] = &SECRET;
+    let msg_hash = [0x42; 32];
+
+    b.iter(|| {
+        sign(&g, &msg_hash, secret)
+    });
+}
+
+#[bench]
+fn verify_signature(b: &mut Bencher) {
+    let g = ECPointG::new();
+    let secret: &[u8] = &SECRET;
+    let msg_hash = [0x42; 32];
+    let public_key = create_public_key(&g, secret).unwrap();
+    let (signature, _) = sign(&g, &msg_hash, secret).unwrap();
+
+    b.iter(|| {
+        verify(&msg_hash, &signature, &public_key)
+    });
+}
+
+#[bench]
+fn recover_public_key(b: &mut Bencher) {
+    let g = ECPointG::new();
+    let secret: &[u8] = &SECRET;
+    let msg_hash = [0x42; 32];
+    let (signature, recid) = sign(&g, &msg_hash, secret).unwrap();
+    let sig64 = signature.to_bytes();
+
+    b.iter(|| {
+        recover(&msg_hash, &sig64, recid)
+    });
+}
+
+#[bench]
+fn multiply_point(b: &mut Bencher) {
+    let k = Scalar::from_bytes(&SECRET).unwrap();
+    let point = ECPoint::from(G.mul_glv(&k));
+
+    b.iter(|| {
+        point.mul_glv(&k)
+    });
+}
+
+#[bench]
+fn multiply_point_ct(b: &mut Bencher) {
+    let k = Scalar::from_bytes(&SECRET).unwrap();
+    let point = ECPoint::from(G.mul_glv(&k));
+
+    b.iter(|| {
+        point * &k
+    });
+}
+
+fn multi_mul_terms(n: usize) -> Vec<(ECPoint, Scalar)> {
+    let mut point = ECJPoint::from(G);
+    let mut k = Scalar::from_bytes_reduced(&[0x3c; 32]);
+
+    (0..n).map(|_| {
+        point += &G;
+        point.double();
+        k = k * &k + &Scalar::from(1);
+
+        (ECPoint::from(point), k)
+    }).collect()
+}
+
+#[bench]
+fn multi_mul_strauss_64(b: &mut Bencher) {
+    let terms = multi_mul_terms(64);
+    let mut scratch = Box::new(MultiMulScratch::new());
+
+    b.iter(|| {
+        strauss(&terms, &mut scratch)
+    });
+}
+
+#[bench]
+fn multi_mul_pippenger_64(b: &mut Bencher) {
+    let terms = multi_mul_terms(64);
+    let mut scratch = Box::new(MultiMulScratch::new());
+
+    b.iter(|| {
+        pippenger(&terms, &mut scratch)
+    });
+}
@@ -0,0 +1,56 @@
+//! With one of the `static-table` features, precomputes the `ECPointG`
+//! table into `$OUT_DIR/ecpoint_g.rs`. Only the small `table_gen` module
+//! and the `words` helpers it needs are compiled into this script.
+
+extern crate core;
+
+use std::env;
+use std::fs;
+use std::path::Path;
+
+#[allow(dead_code)]
+#[path = "src/words.rs"]
+mod words;
+#[path = "src/table_gen.rs"]
+mod table_gen;
+
+fn main() {
+	println!("cargo:rerun-if-changed=build.rs");
+	println!("cargo:rerun-if-changed=src/words.rs");
+	println!("cargo:rerun-if-changed=src/table_gen.rs");
+
+	// the largest table wins when several features are enabled
+	let name = if feature("STATIC_TABLE_LARGE") {
+		"w8"
+	} else if feature("STATIC_TABLE") {
+		"w4"
+	} else if feature("STATIC_TABLE_SMALL") {
+		"w2"
+	} else {
+		return;
+	};
+
+	let &(_, bits, spacing) = table_gen::WINDOWS
+		.iter()
+		.find(|&&(window, _, _)| window == name)
+		.expect("every feature has a window");
+
+	let mut source = String::new();
+
+	table_gen::write_table(&mut source, bits, spacing)
+		.expect("writing to a String can't fail");
+
+	// the table can't be gated on the features themselves, which are
+	// also enabled while compiling this script
+	println!("cargo:rustc-cfg=static_table");
+	println!("cargo:rustc-cfg=static_table=\"{}\"", name);
+
+	let out_dir = env::var_os("OUT_DIR").expect("OUT_DIR is set by cargo");
+
+	fs::write(Path::new(&out_dir).join("ecpoint_g.rs"), source)
+		.expect("failed to write the ECPointG table");
+}
+
+fn feature(name: &str) -> bool {
+	env::var_os(format!("CARGO_FEATURE_{}", name)).is_some()
+}
@@ -0,0 +1,202 @@
+//! Base58 and Base58Check encoding with the Bitcoin alphabet, writing
+//! into caller provided buffers so that no allocation is needed.
+
+use core::str;
+use error::Error;
+use sha256::Sha256;
+
+const ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// Encode `data` into `out`. The result takes at most
+/// `ceil(data.len() * log(256) / log(58))` characters, 112 for the 82
+/// bytes of a Base58Check extended key (`bip32::BASE58_LEN`). Fails
+/// with `InvalidLength` if it doesn't fit.
+pub fn encode<'a>(data: &[u8], out: &'a mut [u8]) -> Result<&'a str, Error> {
+	encode_parts(data, &[], out)
+}
+
+/// Encode `data` followed by the first 4 bytes of its double SHA-256.
+/// `out` must be able to hold the encoding of `data.len() + 4` bytes,
+/// as for `encode`.
+pub fn encode_check<'a>(data: &[u8], out: &'a mut [u8]) -> Result<&'a str, Error> {
+	encode_parts(data, &checksum(data), out)
+}
+
+/// Decode `s` into `out`, returning the number of bytes written. Fails
+/// with `InvalidBase58` on a character outside of the alphabet and with
+/// `InvalidLength` if the result doesn't fit.
+pub fn decode(s: &str, out: &mut [u8]) -> Result<usize, Error> {
+	// digits are accumulated little-endian, and reversed at the end
+	let mut len = 0;
+
+	for &c in s.as_bytes() {
+		let mut carry = match ALPHABET.iter().position(|&a| a == c) {
+			Some(digit) => digit as u32,
+			None => return Err(Error::InvalidBase58)
+		};
+
+		for byte in out[..len].iter_mut() {
+			carry += *byte as u32 * 58;
+			*byte = carry as u8;
+			carry >>= 8;
+		}
+
+		while carry > 0 {
+			if len == out.len() {
+				return Err(Error::InvalidLength);
+			}
+
+			out[len] = carry as u8;
+			len += 1;
+			carry >>= 8;
+		}
+	}
+
+	// each leading '1' stands for a leading zero byte
+	for _ in s.bytes().take_while(|&c| c == ALPHABET[0]) {
+		if len == out.len() {
+			return Err(Error::InvalidLength);
+		}
+
+		out[len] = 0;
+		len += 1;
+	}
+
+	out[..len].reverse();
+
+	Ok(len)
+}
+
+/// Decode `s` and verify the trailing 4 bytes checksum, returning the
+/// length of the payload before it. `out` must have room for the
+/// checksum too. Fails with `InvalidBase58` if the checksum is missing
+/// or doesn't match.
+pub fn decode_check(s: &str, out: &mut [u8]) -> Result<usize, Error> {
+	let len = decode(s, out)?;
+
+	if len < 4 {
+		return Err(Error::InvalidBase58);
+	}
+
+	let (data, check) = out[..len].split_at(len - 4);
+
+	if checksum(data) != check {
+		return Err(Error::InvalidBase58);
+	}
+
+	Ok(len - 4)
+}
+
+fn checksum(data: &[u8]) -> [u8; 4] {
+	let hash = Sha256::digest(&Sha256::digest(data));
+
+	[hash[0], hash[1], hash[2], hash[3]]
+}
+
+/// Encode the concatenation of `head` and `tail`
+fn encode_parts<'a>(head: &[u8], tail: &[u8], out: &'a mut [u8]) -> Result<&'a str, Error> {
+	let zeros = head.iter().chain(tail).take_while(|&&byte| byte == 0).count();
+	let mut len = 0;
+
+	for &byte in head.iter().chain(tail).skip(zeros) {
+		let mut carry = byte as u32;
+
+		for digit in out[..len].iter_mut() {
+			carry += (*digit as u32) << 8;
+			*digit = (carry % 58) as u8;
+			carry /= 58;
+		}
+
+		while carry > 0 {
+			if len == out.len() {
+				return Err(Error::InvalidLength);
+			}
+
+			out[len] = (carry % 58) as u8;
+			len += 1;
+			carry /= 58;
+		}
+	}
+
+	if out.len() - len < zeros {
+		return Err(Error::InvalidLength);
+	}
+
+	for digit in out[len..len + zeros].iter_mut() {
+		*digit = 0;
+	}
+
+	len += zeros;
+
+	let out = &mut out[..len];
+
+	out.reverse();
+
+	for digit in out.iter_mut() {
+		*digit = ALPHABET[*digit as usize];
+	}
+
+	Ok(str::from_utf8(out).expect("contains only characters of the alphabet; qed"))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn base58_round_trip() {
+		let data: &[u8] = &[0x00, 0x00, 0x28, 0x7f, 0xb4, 0xcd];
+		let mut buf = [0u8; 16];
+
+		assert_eq!(encode(data, &mut buf), Ok("11233QC4"));
+
+		let mut out = [0u8; 6];
+
+		assert_eq!(decode("11233QC4", &mut out), Ok(6));
+		assert_eq!(&out[..], data);
+
+		assert_eq!(encode(&[], &mut buf), Ok(""));
+		assert_eq!(encode(b"hello world", &mut buf), Ok("StV1DL6CwTryKyV"));
+		assert_eq!(decode("StV1DL6CwTryKyV", &mut out), Err(Error::InvalidLength));
+		assert_eq!(decode("0OIl", &mut out), Err(Error::InvalidBase58));
+	}
+
+	#[test]
+	fn base58_check() {
+		// P2PKH address of the all zeroes hash160
+		let mut data = [0u8; 21];
+		let mut buf = [0u8; 64];
+
+		let address = encode_check(&data, &mut buf);
+
+		assert_eq!(address, Ok("1111111111111111111114oLvT2"));
+
+		let mut out = [0u8; 25];
+
+		assert_eq!(decode_check("1111111111111111111114oLvT2", &mut out), Ok(21));
+		assert_eq!(&out[..21], &data[..]);
+
+		assert_eq!(decode_check("1111111111111111111114oLvT3", &mut out), Err(Error::InvalidBase58));
+		assert_eq!(decode_check("1", &mut out), Err(Error::InvalidBase58));
+
+		data[0] = 5;
+
+		let mut buf = [0u8; 64];
+		let encoded = encode_check(&data, &mut buf).unwrap();
+
+		assert_eq!(decode_check(encoded, &mut out), Ok(21));
+		assert_eq!(&out[..21], &data[..]);
+	}
+
+	#[test]
+	fn base58_short_buffer() {
+		let mut buf = [0u8; 112];
+
+		assert_eq!(encode(&[0xff; 82], &mut buf).map(str::len), Ok(112));
+		assert_eq!(encode(&[0xff; 82], &mut buf[..111]), Err(Error::InvalidLength));
+
+		// leading zeros that don't fit
+		assert_eq!(encode(&[0, 0, 1], &mut buf[..3]).map(str::len), Ok(3));
+		assert_eq!(encode(&[0, 0, 1], &mut buf[..2]), Err(Error::InvalidLength));
+	}
+}
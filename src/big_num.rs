@@ -1,8 +1,20 @@
+// Style lints the original code predates
+#![allow(
+	clippy::needless_lifetimes,
+	clippy::redundant_static_lifetimes,
+	clippy::needless_borrow,
+	clippy::op_ref,
+	clippy::bool_assert_comparison
+)]
+
 use core::ops::{Add, AddAssign, Sub, SubAssign, Mul, MulAssign, Shr, ShrAssign};
 use core::fmt::{self, Debug};
 use core::cmp::Ordering;
 use core::str;
+use core::convert::TryFrom;
 use naf::NAF;
+use error::Error;
+use words;
 
 #[derive(Copy, Clone, Eq)]
 pub struct BigNum {
@@ -278,7 +290,7 @@ impl<'a> MulAssign<&'a BigNum> for BigNum {
 		}
 
 		if rhs.len == 1 {
-			self.mul(rhs.words[0]);
+			*self *= rhs.words[0];
 			return;
 		}
 
@@ -380,45 +392,20 @@ impl ShrAssign<u32> for BigNum {
 	}
 }
 
-#[inline]
-fn read_u32(buf: &[u8]) -> u32 {
-	assert!(buf.len() == 4);
-
-	// Note: while this will work on all archs, WASM is _always_ little-endian
-	u32::from_be(unsafe { *(buf.as_ptr() as *const u32) })
-}
-
-#[inline]
-fn write_u32(val: u32, buf: &mut [u8]) {
-	assert!(buf.len() == 4);
-
-	unsafe {
-		let ptr = buf.as_mut_ptr() as *mut u32;
-		*ptr = u32::to_be(val);
-	}
-}
+/// Reads a big-endian 32 bytes integer, failing if the slice is not
+/// exactly 32 bytes long.
+impl<'a> TryFrom<&'a [u8]> for BigNum {
+	type Error = Error;
 
-impl<'a> From<&'a [u8]> for BigNum {
-	fn from(buf: &'a [u8]) -> Self {
-		let mut bn = BigNum {
-			negative: false,
-			len: buf.len() / 4,
-			words: [
-				read_u32(&buf[28..32]),
-				read_u32(&buf[24..28]),
-				read_u32(&buf[20..24]),
-				read_u32(&buf[16..20]),
-				read_u32(&buf[12..16]),
-				read_u32(&buf[8..12]),
-				read_u32(&buf[4..8]),
-				read_u32(&buf[0..4]),
-				0, 0, 0, 0, 0, 0, 0, 0
-			]
-		};
+	fn try_from(buf: &'a [u8]) -> Result<Self, Error> {
+		if buf.len() != 32 {
+			return Err(Error::InvalidLength);
+		}
 
-		bn.strip();
+		let mut bytes = [0u8; 32];
+		bytes.copy_from_slice(buf);
 
-		bn
+		Ok(BigNum::from_be_bytes(&bytes))
 	}
 }
 
@@ -436,6 +423,29 @@ impl From<u32> for BigNum {
 }
 
 impl BigNum {
+	/// Reads a big-endian 32 bytes integer
+	pub fn from_be_bytes(bytes: &[u8; 32]) -> BigNum {
+		BigNum::from_words(&words::from_be_bytes(bytes))
+	}
+
+	/// Reads a little-endian 32 bytes integer
+	pub fn from_le_bytes(bytes: &[u8; 32]) -> BigNum {
+		BigNum::from_words(&words::from_le_bytes(bytes))
+	}
+
+	fn from_words(words: &[u32; 8]) -> BigNum {
+		let mut bn = BigNum {
+			negative: false,
+			len: 8,
+			words: [0; 16]
+		};
+
+		bn.words[..8].copy_from_slice(words);
+		bn.strip();
+
+		bn
+	}
+
 	fn mul8x8(&mut self, rhs: &BigNum) {
 		let mut c = 0;
 		let mut lo;
@@ -854,15 +864,20 @@ impl BigNum {
 		high
 	}
 
-	pub fn write_bytes_to(&self, buf: &mut [u8]) {
-		write_u32(self.words[0], &mut buf[28..32]);
-		write_u32(self.words[1], &mut buf[24..28]);
-		write_u32(self.words[2], &mut buf[20..24]);
-		write_u32(self.words[3], &mut buf[16..20]);
-		write_u32(self.words[4], &mut buf[12..16]);
-		write_u32(self.words[5], &mut buf[8..12]);
-		write_u32(self.words[6], &mut buf[4..8]);
-		write_u32(self.words[7], &mut buf[0..4]);
+	/// Big-endian bytes of the lower 256 bits
+	pub fn to_be_bytes(self) -> [u8; 32] {
+		let mut words = [0u32; 8];
+		words.copy_from_slice(&self.words[..8]);
+
+		words::to_be_bytes(&words)
+	}
+
+	/// Little-endian bytes of the lower 256 bits
+	pub fn to_le_bytes(self) -> [u8; 32] {
+		let mut words = [0u32; 8];
+		words.copy_from_slice(&self.words[..8]);
+
+		words::to_le_bytes(&words)
 	}
 
 	pub fn double(&mut self) {
@@ -1044,6 +1059,66 @@ impl BigNum {
 		}
 	}
 
+	/// Modular inverse of `self` for any odd modulus `m`, in variable
+	/// time. Like `red_invm`, but fully reduces the result by `m`. Only
+	/// an oracle for the `modinv` tests.
+	#[cfg(test)]
+	pub(crate) fn invm(mut self, m: &BigNum) -> BigNum {
+		let mut b = *m;
+
+		let mut x1 = ONE;
+		let mut x2 = ZERO;
+
+		while self > 1 && b > 1 {
+			let a_zeros = self.words[0].trailing_zeros();
+
+			if a_zeros != 0 {
+				self >>= a_zeros;
+				for _ in 0..a_zeros {
+					if x1.is_odd() {
+						x1 += m;
+					}
+					x1 >>= 1;
+				}
+			}
+
+			let b_zeros = b.words[0].trailing_zeros();
+			if b_zeros != 0 {
+				b >>= b_zeros;
+				for _ in 0..b_zeros {
+					if x2.is_odd() {
+						x2 += m;
+					}
+					x2 >>= 1;
+				}
+			}
+
+			if self >= b {
+				self -= &b;
+				x1 -= &x2;
+			} else {
+				b -= &self;
+				x2 -= &x1;
+			}
+		}
+
+		if self == 1 {
+			self = x1;
+		} else {
+			self = x2;
+		}
+
+		while self.negative {
+			self += m;
+		}
+
+		while &self >= m {
+			self -= m;
+		}
+
+		self
+	}
+
 	pub fn red_sqr(&self) -> BigNum {
 		let mut res = *self * self;
 		res.red_reduce();
@@ -1206,8 +1281,8 @@ mod tests {
 			0x42,0xe6,0x5f,0x93,0x7d,0x7e
 		];
 
-		assert_eq!(n, BigNum::from(expected_bytes));
-		assert_eq!(nm, BigNum::from(expected_bytes));
+		assert_eq!(n, BigNum::try_from(expected_bytes).unwrap());
+		assert_eq!(nm, BigNum::try_from(expected_bytes).unwrap());
 	}
 
 	#[test]
@@ -1220,7 +1295,7 @@ mod tests {
 			0x5e,0x8b,0xd0,0x36,0x3d,0x70
 		];
 
-		assert_eq!(n, BigNum::from(expected_bytes));
+		assert_eq!(n, BigNum::try_from(expected_bytes).unwrap());
 	}
 
 	#[test]
@@ -1232,7 +1307,7 @@ mod tests {
 			0x00,0x00,0x01,0x45,0x51,0x23,0x19,0x50,0xb7,0x5f,0xc4,0x40,0x2d,
 			0xa1,0x73,0x2f,0xc9,0xbe,0xbe,
 		];
-		let mut expected = BigNum::from(expected_bytes);
+		let mut expected = BigNum::try_from(expected_bytes).unwrap();
 		expected.negative = true;
 
 		assert_eq!(n, expected);
@@ -1251,8 +1326,8 @@ mod tests {
 			0x5b,0x42,0xe6,0x5f,0x93,0x7d
 		];
 
-		assert_eq!(n, BigNum::from(expected_bytes));
-		assert_eq!(nm, BigNum::from(expected_bytes));
+		assert_eq!(n, BigNum::try_from(expected_bytes).unwrap());
+		assert_eq!(nm, BigNum::try_from(expected_bytes).unwrap());
 	}
 
 	#[test]
@@ -1263,14 +1338,29 @@ mod tests {
 			0x5E,0x8C,0xD0,0x36,0x41,0x41
 		];
 
-		let mut roundtrip = [0u8; 32];
-		let bn = BigNum::from(bytes);
-		bn.write_bytes_to(&mut roundtrip);
+		let bn = BigNum::try_from(bytes).unwrap();
+		let roundtrip = bn.to_be_bytes();
 
 		assert_eq!(&bn, N);
 		assert_eq!(bytes, roundtrip);
 	}
 
+	#[test]
+	fn big_num_from_bytes() {
+		let bytes = N.to_be_bytes();
+
+		assert_eq!(BigNum::try_from(&bytes[..31]), Err(Error::InvalidLength));
+		assert_eq!(BigNum::try_from(&[0u8; 33][..]), Err(Error::InvalidLength));
+		assert_eq!(BigNum::try_from(&[][..]), Err(Error::InvalidLength));
+
+		let mut le = bytes;
+		le.reverse();
+
+		assert_eq!(&BigNum::from_le_bytes(&le), N);
+		assert_eq!(N.to_le_bytes(), le);
+		assert_eq!(BigNum::from_be_bytes(&[0; 32]), 0);
+	}
+
 	#[test]
 	fn produces_valid_nh() {
 		let nh = *N >> 1;
@@ -1286,9 +1376,8 @@ mod tests {
 			0xA1,0x73,0x2F,0xC9,0xBE,0xBF
 		];
 
-		let mut roundtrip = [0u8; 32];
-		let bn = BigNum::from(bytes);
-		bn.write_bytes_to(&mut roundtrip);
+		let bn = BigNum::try_from(bytes).unwrap();
+		let roundtrip = bn.to_be_bytes();
 
 		assert_eq!(&bn, NC);
 		assert_eq!(bytes, roundtrip);
@@ -1302,9 +1391,8 @@ mod tests {
 			0xFF,0xFE,0xFF,0xFF,0xFC,0x2F
 		];
 
-		let mut roundtrip = [0u8; 32];
-		let bn = BigNum::from(bytes);
-		bn.write_bytes_to(&mut roundtrip);
+		let bn = BigNum::try_from(bytes).unwrap();
+		let roundtrip = bn.to_be_bytes();
 
 		assert_eq!(&bn, P);
 		assert_eq!(bytes, roundtrip);
@@ -1363,12 +1451,20 @@ mod tests {
 
 		let mut buf = [0u8; 64];
 
-		low.write_bytes_to(&mut buf[32..]);
-		high.write_bytes_to(&mut buf[..32]);
+		buf[32..].copy_from_slice(&low.to_be_bytes());
+		buf[..32].copy_from_slice(&high.to_be_bytes());
 
 		assert_eq!(&buf[..], expected_bytes);
 	}
 
+	#[test]
+	fn multiply_one_word() {
+		let three = BigNum::from(3);
+
+		assert_eq!(*NC * &three, *NC + NC + NC);
+		assert_eq!(*N * &three, *N * 3);
+	}
+
 	#[test]
 	fn mul_k() {
 		let mut n = *NC;
@@ -1380,7 +1476,7 @@ mod tests {
 			0xe7,0x58,0x60,0xf0,0xf6,0xef
 		];
 
-		assert_eq!(n, BigNum::from(expected_bytes));
+		assert_eq!(n, BigNum::try_from(expected_bytes).unwrap());
 	}
 
 	#[test]
@@ -1394,7 +1490,7 @@ mod tests {
 			0x96,0xb5,0x28,0xe2,0xdc,0xcc
 		];
 
-		assert_eq!(reduced, BigNum::from(expected_bytes));
+		assert_eq!(reduced, BigNum::try_from(expected_bytes).unwrap());
 	}
 
 	#[test]
@@ -1407,7 +1503,7 @@ mod tests {
 			0x5e,0x8b,0xd0,0x36,0x3d,0x70
 		];
 
-		assert_eq!(n, BigNum::from(expected_bytes));
+		assert_eq!(n, BigNum::try_from(expected_bytes).unwrap());
 	}
 
 	#[test]
@@ -1420,7 +1516,7 @@ mod tests {
 			0xdf,0x89,0x80,0x6e,0x26,0xd1
 		];
 
-		assert_eq!(n, BigNum::from(expected_bytes));
+		assert_eq!(n, BigNum::try_from(expected_bytes).unwrap());
 	}
 
 	#[test]
@@ -1433,7 +1529,7 @@ mod tests {
 			0x5e,0x8c,0xd0,0x36,0x41,0x40
 		];
 
-		assert_eq!(bn, BigNum::from(expected_bytes));
+		assert_eq!(bn, BigNum::try_from(expected_bytes).unwrap());
 	}
 
 	#[test]
@@ -0,0 +1,539 @@
+//! BIP32 hierarchical deterministic keys: master key generation from a
+//! seed, private and public child derivation, and the Base58Check
+//! `xprv` / `xpub` serialization.
+
+use core::fmt;
+use error::Error;
+use words;
+use base58;
+use hmac::HmacSha512;
+use sha256::Sha256;
+use ripemd160::Ripemd160;
+use ec_point_g::{ECPointG, Window};
+use secret_key::SecretKey;
+use public_key::PublicKey;
+use tweak::{secret_tweak_add, public_tweak_add};
+
+/// Child numbers from this one on are hardened, written `i'` or `iH`
+pub const HARDENED: u32 = 1 << 31;
+
+/// Version bytes of mainnet secret keys, `xprv...`
+pub const XPRV: [u8; 4] = [0x04, 0x88, 0xad, 0xe4];
+
+/// Version bytes of mainnet public keys, `xpub...`
+pub const XPUB: [u8; 4] = [0x04, 0x88, 0xb2, 0x1e];
+
+/// Version bytes of testnet secret keys, `tprv...`
+pub const TPRV: [u8; 4] = [0x04, 0x35, 0x83, 0x94];
+
+/// Version bytes of testnet public keys, `tpub...`
+pub const TPUB: [u8; 4] = [0x04, 0x35, 0x87, 0xcf];
+
+/// Length of the serialization, before Base58Check encoding
+pub const SERIALIZED_LEN: usize = 78;
+
+/// Buffer size needed by `to_base58`
+pub const BASE58_LEN: usize = 112;
+
+/// An extended secret key. The chain code is wiped on drop along with
+/// the key, and neither is shown by `Debug`.
+#[derive(Clone)]
+pub struct ExtendedSecretKey {
+	/// 0 for the master key
+	pub depth: u8,
+	pub parent_fingerprint: [u8; 4],
+	pub child_number: u32,
+	pub chain_code: [u8; 32],
+	pub secret_key: SecretKey
+}
+
+/// An extended public key, which can only derive non-hardened children.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ExtendedPublicKey {
+	/// 0 for the master key
+	pub depth: u8,
+	pub parent_fingerprint: [u8; 4],
+	pub child_number: u32,
+	pub chain_code: [u8; 32],
+	pub public_key: PublicKey
+}
+
+impl ExtendedSecretKey {
+	/// Master key of a 16 to 64 bytes seed, fails with `InvalidLength`
+	/// for other lengths. An unusable seed fails with the error of
+	/// `SecretKey::from_bytes`, with a probability below 2^-127.
+	pub fn new_master(seed: &[u8]) -> Result<ExtendedSecretKey, Error> {
+		if seed.len() < 16 || seed.len() > 64 {
+			return Err(Error::InvalidLength);
+		}
+
+		let mut hmac = HmacSha512::new(b"Bitcoin seed");
+		hmac.update(seed);
+
+		let mut i = hmac.finalize();
+		let (mut secret, chain_code) = split(&i);
+		let secret_key = SecretKey::from_bytes(&secret);
+
+		words::wipe(&mut i);
+		words::wipe(&mut secret);
+
+		Ok(ExtendedSecretKey {
+			depth: 0,
+			parent_fingerprint: [0; 4],
+			child_number: 0,
+			chain_code,
+			secret_key: secret_key?
+		})
+	}
+
+	/// Derive the child `child_number`, hardened if it's `HARDENED` or
+	/// above. Fails with `InvalidTweak` for the about 2^-127 of indices
+	/// that give an invalid key, the next index should be used then.
+	pub fn derive_child<W: Window>(&self, g: &ECPointG<W>, child_number: u32) -> Result<ExtendedSecretKey, Error> {
+		let public_key = self.secret_key.public_key(g);
+		let mut hmac = HmacSha512::new(&self.chain_code);
+
+		if child_number >= HARDENED {
+			let mut secret = self.secret_key.to_bytes();
+
+			hmac.update(&[0]);
+			hmac.update(&secret);
+
+			words::wipe(&mut secret);
+		} else {
+			hmac.update(&public_key.serialize_compressed());
+		}
+
+		hmac.update(&child_number.to_be_bytes());
+
+		let mut i = hmac.finalize();
+		let (mut tweak, chain_code) = split(&i);
+		let secret_key = secret_tweak_add(&self.secret_key, &tweak);
+
+		words::wipe(&mut i);
+		words::wipe(&mut tweak);
+
+		Ok(ExtendedSecretKey {
+			depth: self.depth.checked_add(1).ok_or(Error::InvalidExtendedKey)?,
+			parent_fingerprint: fingerprint(&public_key),
+			child_number,
+			chain_code,
+			secret_key: secret_key?
+		})
+	}
+
+	/// Derive every child number of `path` in turn, for example
+	/// `[44 | HARDENED, HARDENED, HARDENED, 0, 7]` for `m/44'/0'/0'/0/7`.
+	pub fn derive_path<W: Window>(&self, g: &ECPointG<W>, path: &[u32]) -> Result<ExtendedSecretKey, Error> {
+		let mut key = self.clone();
+
+		for &child_number in path {
+			key = key.derive_child(g, child_number)?;
+		}
+
+		Ok(key)
+	}
+
+	/// The extended public key with the same chain code and position
+	pub fn to_public<W: Window>(&self, g: &ECPointG<W>) -> ExtendedPublicKey {
+		ExtendedPublicKey {
+			depth: self.depth,
+			parent_fingerprint: self.parent_fingerprint,
+			child_number: self.child_number,
+			chain_code: self.chain_code,
+			public_key: self.secret_key.public_key(g)
+		}
+	}
+
+	/// First 4 bytes of the HASH160 of the compressed public key
+	pub fn fingerprint<W: Window>(&self, g: &ECPointG<W>) -> [u8; 4] {
+		fingerprint(&self.secret_key.public_key(g))
+	}
+
+	/// The 78 bytes serialization. It contains the secret key, so it's
+	/// up to the caller to wipe it.
+	pub fn serialize(&self, version: [u8; 4]) -> [u8; SERIALIZED_LEN] {
+		let mut key = [0u8; 33];
+		key[1..].copy_from_slice(&self.secret_key.to_bytes());
+
+		let bytes = serialize(version, self.depth, &self.parent_fingerprint, self.child_number, &self.chain_code, &key);
+
+		words::wipe(&mut key);
+
+		bytes
+	}
+
+	/// Base58Check encoding of `serialize`, written to `out`
+	pub fn to_base58<'a>(&self, version: [u8; 4], out: &'a mut [u8; BASE58_LEN]) -> &'a str {
+		let mut bytes = self.serialize(version);
+		let encoded = base58::encode_check(&bytes, out).expect("BASE58_LEN fits any extended key; qed");
+
+		words::wipe(&mut bytes);
+
+		encoded
+	}
+
+	/// Parse a 78 bytes serialization, failing with `InvalidExtendedKey`
+	/// if the version isn't `version`, the key isn't prefixed by a zero
+	/// byte or a master key has a parent. Invalid secret keys fail with
+	/// the error of `SecretKey::from_bytes`.
+	pub fn deserialize(bytes: &[u8; SERIALIZED_LEN], version: [u8; 4]) -> Result<ExtendedSecretKey, Error> {
+		let (depth, parent_fingerprint, child_number, chain_code) = deserialize(bytes, version)?;
+
+		if bytes[45] != 0 {
+			return Err(Error::InvalidExtendedKey);
+		}
+
+		let mut secret = [0u8; 32];
+		secret.copy_from_slice(&bytes[46..]);
+
+		let secret_key = SecretKey::from_bytes(&secret);
+
+		words::wipe(&mut secret);
+
+		Ok(ExtendedSecretKey {
+			depth,
+			parent_fingerprint,
+			child_number,
+			chain_code,
+			secret_key: secret_key?
+		})
+	}
+
+	/// Parse the Base58Check encoding, see `deserialize`
+	pub fn from_base58(s: &str, version: [u8; 4]) -> Result<ExtendedSecretKey, Error> {
+		let mut bytes = [0u8; SERIALIZED_LEN];
+		let res = decode_base58(s, &mut bytes).and_then(|_| ExtendedSecretKey::deserialize(&bytes, version));
+
+		words::wipe(&mut bytes);
+
+		res
+	}
+}
+
+impl Drop for ExtendedSecretKey {
+	fn drop(&mut self) {
+		words::wipe(&mut self.chain_code);
+	}
+}
+
+impl fmt::Debug for ExtendedSecretKey {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("ExtendedSecretKey")
+			.field("depth", &self.depth)
+			.field("parent_fingerprint", &self.parent_fingerprint)
+			.field("child_number", &self.child_number)
+			.finish_non_exhaustive()
+	}
+}
+
+impl ExtendedPublicKey {
+	/// Derive the non-hardened child `child_number`, fails with
+	/// `HardenedDerivation` if it's `HARDENED` or above. Fails with
+	/// `InvalidTweak` for the about 2^-127 of indices that give an
+	/// invalid key, the next index should be used then.
+	pub fn derive_child<W: Window>(&self, g: &ECPointG<W>, child_number: u32) -> Result<ExtendedPublicKey, Error> {
+		if child_number >= HARDENED {
+			return Err(Error::HardenedDerivation);
+		}
+
+		let mut hmac = HmacSha512::new(&self.chain_code);
+		hmac.update(&self.public_key.serialize_compressed());
+		hmac.update(&child_number.to_be_bytes());
+
+		let (tweak, chain_code) = split(&hmac.finalize());
+
+		Ok(ExtendedPublicKey {
+			depth: self.depth.checked_add(1).ok_or(Error::InvalidExtendedKey)?,
+			parent_fingerprint: self.fingerprint(),
+			child_number,
+			chain_code,
+			public_key: public_tweak_add(g, &self.public_key, &tweak)?
+		})
+	}
+
+	/// Derive every child number of `path` in turn, none of which can
+	/// be hardened.
+	pub fn derive_path<W: Window>(&self, g: &ECPointG<W>, path: &[u32]) -> Result<ExtendedPublicKey, Error> {
+		let mut key = *self;
+
+		for &child_number in path {
+			key = key.derive_child(g, child_number)?;
+		}
+
+		Ok(key)
+	}
+
+	/// HASH160 of the compressed public key
+	pub fn identifier(&self) -> [u8; 20] {
+		hash160(&self.public_key.serialize_compressed())
+	}
+
+	/// First 4 bytes of `identifier`
+	pub fn fingerprint(&self) -> [u8; 4] {
+		fingerprint(&self.public_key)
+	}
+
+	/// The 78 bytes serialization
+	pub fn serialize(&self, version: [u8; 4]) -> [u8; SERIALIZED_LEN] {
+		serialize(version, self.depth, &self.parent_fingerprint, self.child_number, &self.chain_code, &self.public_key.serialize_compressed())
+	}
+
+	/// Base58Check encoding of `serialize`, written to `out`
+	pub fn to_base58<'a>(&self, version: [u8; 4], out: &'a mut [u8; BASE58_LEN]) -> &'a str {
+		base58::encode_check(&self.serialize(version), out).expect("BASE58_LEN fits any extended key; qed")
+	}
+
+	/// Parse a 78 bytes serialization, failing with `InvalidExtendedKey`
+	/// if the version isn't `version` or a master key has a parent.
+	/// Invalid public keys fail with the error of `PublicKey::parse`.
+	pub fn deserialize(bytes: &[u8; SERIALIZED_LEN], version: [u8; 4]) -> Result<ExtendedPublicKey, Error> {
+		let (depth, parent_fingerprint, child_number, chain_code) = deserialize(bytes, version)?;
+
+		Ok(ExtendedPublicKey {
+			depth,
+			parent_fingerprint,
+			child_number,
+			chain_code,
+			public_key: PublicKey::parse(&bytes[45..])?
+		})
+	}
+
+	/// Parse the Base58Check encoding, see `deserialize`
+	pub fn from_base58(s: &str, version: [u8; 4]) -> Result<ExtendedPublicKey, Error> {
+		let mut bytes = [0u8; SERIALIZED_LEN];
+
+		decode_base58(s, &mut bytes)?;
+
+		ExtendedPublicKey::deserialize(&bytes, version)
+	}
+}
+
+fn hash160(data: &[u8]) -> [u8; 20] {
+	Ripemd160::digest(&Sha256::digest(data))
+}
+
+fn fingerprint(public_key: &PublicKey) -> [u8; 4] {
+	let hash = hash160(&public_key.serialize_compressed());
+
+	[hash[0], hash[1], hash[2], hash[3]]
+}
+
+/// Split the HMAC output into the key tweak and the chain code
+fn split(i: &[u8; 64]) -> ([u8; 32], [u8; 32]) {
+	let mut left = [0u8; 32];
+	let mut right = [0u8; 32];
+
+	left.copy_from_slice(&i[..32]);
+	right.copy_from_slice(&i[32..]);
+
+	(left, right)
+}
+
+fn serialize(version: [u8; 4], depth: u8, parent_fingerprint: &[u8; 4], child_number: u32, chain_code: &[u8; 32], key: &[u8; 33]) -> [u8; SERIALIZED_LEN] {
+	let mut bytes = [0u8; SERIALIZED_LEN];
+
+	bytes[0..4].copy_from_slice(&version);
+	bytes[4] = depth;
+	bytes[5..9].copy_from_slice(parent_fingerprint);
+	bytes[9..13].copy_from_slice(&child_number.to_be_bytes());
+	bytes[13..45].copy_from_slice(chain_code);
+	bytes[45..].copy_from_slice(key);
+
+	bytes
+}
+
+/// Check and read everything but the key
+fn deserialize(bytes: &[u8; SERIALIZED_LEN], version: [u8; 4]) -> Result<(u8, [u8; 4], u32, [u8; 32]), Error> {
+	if bytes[0..4] != version {
+		return Err(Error::InvalidExtendedKey);
+	}
+
+	let depth = bytes[4];
+	let mut parent_fingerprint = [0u8; 4];
+	let mut child_number = [0u8; 4];
+	let mut chain_code = [0u8; 32];
+
+	parent_fingerprint.copy_from_slice(&bytes[5..9]);
+	child_number.copy_from_slice(&bytes[9..13]);
+	chain_code.copy_from_slice(&bytes[13..45]);
+
+	let child_number = u32::from_be_bytes(child_number);
+
+	if depth == 0 && (parent_fingerprint != [0; 4] || child_number != 0) {
+		return Err(Error::InvalidExtendedKey);
+	}
+
+	Ok((depth, parent_fingerprint, child_number, chain_code))
+}
+
+/// Base58Check decoding of exactly `SERIALIZED_LEN` bytes
+fn decode_base58(s: &str, bytes: &mut [u8; SERIALIZED_LEN]) -> Result<(), Error> {
+	let mut buf = [0u8; SERIALIZED_LEN + 4];
+	let res = base58::decode_check(s, &mut buf);
+
+	bytes.copy_from_slice(&buf[..SERIALIZED_LEN]);
+	words::wipe(&mut buf);
+
+	match res? {
+		SERIALIZED_LEN => Ok(()),
+		_ => Err(Error::InvalidLength)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	// BIP32 test vector 1, seed 000102030405060708090a0b0c0d0e0f
+	const VECTOR_1: [(&[u32], &str, &str); 6] = [
+		(
+			&[],
+			"xprv9s21ZrQH143K3QTDL4LXw2F7HEK3wJUD2nW2nRk4stbPy6cq3jPPqjiChkVvvNKmPGJxWUtg6LnF5kejMRNNU3TGtRBeJgk33yuGBxrMPHi",
+			"xpub661MyMwAqRbcFtXgS5sYJABqqG9YLmC4Q1Rdap9gSE8NqtwybGhePY2gZ29ESFjqJoCu1Rupje8YtGqsefD265TMg7usUDFdp6W1EGMcet8"
+		),
+		(
+			&[HARDENED],
+			"xprv9uHRZZhk6KAJC1avXpDAp4MDc3sQKNxDiPvvkX8Br5ngLNv1TxvUxt4cV1rGL5hj6KCesnDYUhd7oWgT11eZG7XnxHrnYeSvkzY7d2bhkJ7",
+			"xpub68Gmy5EdvgibQVfPdqkBBCHxA5htiqg55crXYuXoQRKfDBFA1WEjWgP6LHhwBZeNK1VTsfTFUHCdrfp1bgwQ9xv5ski8PX9rL2dZXvgGDnw"
+		),
+		(
+			&[HARDENED, 1],
+			"xprv9wTYmMFdV23N2TdNG573QoEsfRrWKQgWeibmLntzniatZvR9BmLnvSxqu53Kw1UmYPxLgboyZQaXwTCg8MSY3H2EU4pWcQDnRnrVA1xe8fs",
+			"xpub6ASuArnXKPbfEwhqN6e3mwBcDTgzisQN1wXN9BJcM47sSikHjJf3UFHKkNAWbWMiGj7Wf5uMash7SyYq527Hqck2AxYysAA7xmALppuCkwQ"
+		),
+		(
+			&[HARDENED, 1, 2 | HARDENED],
+			"xprv9z4pot5VBttmtdRTWfWQmoH1taj2axGVzFqSb8C9xaxKymcFzXBDptWmT7FwuEzG3ryjH4ktypQSAewRiNMjANTtpgP4mLTj34bhnZX7UiM",
+			"xpub6D4BDPcP2GT577Vvch3R8wDkScZWzQzMMUm3PWbmWvVJrZwQY4VUNgqFJPMM3No2dFDFGTsxxpG5uJh7n7epu4trkrX7x7DogT5Uv6fcLW5"
+		),
+		(
+			&[HARDENED, 1, 2 | HARDENED, 2],
+			"xprvA2JDeKCSNNZky6uBCviVfJSKyQ1mDYahRjijr5idH2WwLsEd4Hsb2Tyh8RfQMuPh7f7RtyzTtdrbdqqsunu5Mm3wDvUAKRHSC34sJ7in334",
+			"xpub6FHa3pjLCk84BayeJxFW2SP4XRrFd1JYnxeLeU8EqN3vDfZmbqBqaGJAyiLjTAwm6ZLRQUMv1ZACTj37sR62cfN7fe5JnJ7dh8zL4fiyLHV"
+		),
+		(
+			&[HARDENED, 1, 2 | HARDENED, 2, 1000000000],
+			"xprvA41z7zogVVwxVSgdKUHDy1SKmdb533PjDz7J6N6mV6uS3ze1ai8FHa8kmHScGpWmj4WggLyQjgPie1rFSruoUihUZREPSL39UNdE3BBDu76",
+			"xpub6H1LXWLaKsWFhvm6RVpEL9P4KfRZSW7abD2ttkWP3SSQvnyA8FSVqNTEcYFgJS2UaFcxupHiYkro49S8yGasTvXEYBVPamhGW6cFJodrTHy"
+		)
+	];
+
+	fn seed_1() -> [u8; 16] {
+		let mut seed = [0u8; 16];
+
+		for (i, byte) in seed.iter_mut().enumerate() {
+			*byte = i as u8;
+		}
+
+		seed
+	}
+
+	#[test]
+	fn bip32_vector_1() {
+		let g = ECPointG::new();
+		let master = ExtendedSecretKey::new_master(&seed_1()).unwrap();
+
+		assert_eq!(master.fingerprint(&g), [0x34, 0x42, 0x19, 0x3e]);
+
+		for &(path, xprv, xpub) in VECTOR_1.iter() {
+			let key = master.derive_path(&g, path).unwrap();
+			let public = key.to_public(&g);
+			let mut buf = [0u8; BASE58_LEN];
+
+			assert_eq!(key.to_base58(XPRV, &mut buf), xprv);
+			assert_eq!(public.to_base58(XPUB, &mut buf), xpub);
+
+			let parsed = ExtendedSecretKey::from_base58(xprv, XPRV).unwrap();
+
+			assert_eq!(parsed.serialize(XPRV)[..], key.serialize(XPRV)[..]);
+			assert_eq!(ExtendedPublicKey::from_base58(xpub, XPUB), Ok(public));
+		}
+	}
+
+	#[test]
+	fn bip32_vector_3() {
+		// retention of leading zeros
+		let seed: &[u8] = &[
+			0x4b,0x38,0x15,0x41,0x58,0x3b,0xe4,0x42,0x33,0x46,0xc6,0x43,0x85,
+			0x0d,0xa4,0xb3,0x20,0xe4,0x6a,0x87,0xae,0x3d,0x2a,0x4e,0x6d,0xa1,
+			0x1e,0xba,0x81,0x9c,0xd4,0xac,0xba,0x45,0xd2,0x39,0x31,0x9a,0xc1,
+			0x4f,0x86,0x3b,0x8d,0x5a,0xb5,0xa0,0xd0,0xc6,0x4d,0x2e,0x8a,0x1e,
+			0x7d,0x14,0x57,0xdf,0x2e,0x5a,0x3c,0x51,0xc7,0x32,0x35,0xbe
+		];
+
+		let g = ECPointG::new();
+		let master = ExtendedSecretKey::new_master(seed).unwrap();
+		let child = master.derive_child(&g, HARDENED).unwrap();
+		let mut buf = [0u8; BASE58_LEN];
+
+		assert_eq!(
+			master.to_base58(XPRV, &mut buf),
+			"xprv9s21ZrQH143K25QhxbucbDDuQ4naNntJRi4KUfWT7xo4EKsHt2QJDu7KXp1A3u7Bi1j8ph3EGsZ9Xvz9dGuVrtHHs7pXeTzjuxBrCmmhgC6"
+		);
+		assert_eq!(
+			child.to_base58(XPRV, &mut buf),
+			"xprv9uPDJpEQgRQfDcW7BkF7eTya6RPxXeJCqCJGHuCJ4GiRVLzkTXBAJMu2qaMWPrS7AANYqdq6vcBcBUdJCVVFceUvJFjaPdGZ2y9WACViL4L"
+		);
+	}
+
+	#[test]
+	fn public_derivation() {
+		let g = ECPointG::new();
+		let account = ExtendedSecretKey::new_master(&seed_1()).unwrap()
+			.derive_path(&g, &[44 | HARDENED, HARDENED, HARDENED])
+			.unwrap();
+
+		let xpub = account.to_public(&g);
+		let path = [0, 7, 0x7fff_ffff];
+
+		assert_eq!(xpub.derive_path(&g, &path), Ok(account.derive_path(&g, &path).unwrap().to_public(&g)));
+		assert_eq!(xpub.derive_child(&g, HARDENED), Err(Error::HardenedDerivation));
+		assert_eq!(xpub.derive_child(&g, 0).unwrap().parent_fingerprint, xpub.fingerprint());
+		assert_eq!(&xpub.fingerprint()[..], &xpub.identifier()[..4]);
+	}
+
+	#[test]
+	fn invalid_extended_keys() {
+		let (_, xprv, xpub) = VECTOR_1[1];
+
+		assert_eq!(ExtendedSecretKey::new_master(&[0; 15]).unwrap_err(), Error::InvalidLength);
+		assert_eq!(ExtendedSecretKey::new_master(&[0; 65]).unwrap_err(), Error::InvalidLength);
+
+		assert_eq!(ExtendedSecretKey::from_base58(xprv, TPRV).unwrap_err(), Error::InvalidExtendedKey);
+		assert_eq!(ExtendedSecretKey::from_base58(xpub, XPUB).unwrap_err(), Error::InvalidExtendedKey);
+		assert_eq!(ExtendedPublicKey::from_base58(xprv, XPUB), Err(Error::InvalidExtendedKey));
+		assert_eq!(ExtendedPublicKey::from_base58(&xpub[1..], XPUB), Err(Error::InvalidBase58));
+		assert_eq!(ExtendedPublicKey::from_base58("1111111111111111111114oLvT2", XPUB), Err(Error::InvalidLength));
+
+		// a master key with a parent fingerprint
+		let mut bytes = ExtendedPublicKey::from_base58(VECTOR_1[0].2, XPUB).unwrap().serialize(XPUB);
+		bytes[5] = 1;
+
+		let mut buf = [0u8; BASE58_LEN];
+		let encoded = base58::encode_check(&bytes, &mut buf).unwrap();
+
+		assert_eq!(ExtendedPublicKey::from_base58(encoded, XPUB), Err(Error::InvalidExtendedKey));
+
+		// an uncompressed prefix for the public key
+		bytes[5] = 0;
+		bytes[45] = 0x04;
+
+		let mut buf = [0u8; BASE58_LEN];
+		let encoded = base58::encode_check(&bytes, &mut buf).unwrap();
+
+		assert_eq!(ExtendedPublicKey::from_base58(encoded, XPUB), Err(Error::InvalidPublicKey));
+	}
+
+	#[test]
+	fn extended_secret_key_debug() {
+		extern crate std;
+
+		let master = ExtendedSecretKey::new_master(&seed_1()).unwrap();
+
+		assert_eq!(
+			std::format!("{:?}", master),
+			"ExtendedSecretKey { depth: 0, parent_fingerprint: [0, 0, 0, 0], child_number: 0, .. }"
+		);
+	}
+}
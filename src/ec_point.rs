@@ -1,19 +1,64 @@
-use big_num::{self, BigNum};
-use core::ops::{Add, AddAssign}; //, Sub, SubAssign, Mul, MulAssign, Shr, ShrAssign};
+// Style lints the original code predates
+#![allow(
+	clippy::needless_lifetimes,
+	clippy::needless_return,
+	clippy::redundant_field_names
+)]
 
+use big_num::BigNum;
+use naf::NAF;
+use scalar::Scalar;
+use field::{self, FieldElement};
+use ecj_point::ECJPoint;
+use ec_point_g::{self, Window, Window4};
+use public_key::PublicKey;
+use error::Error;
+use words;
+use core::ops::{Add, AddAssign, Sub, SubAssign, Mul, Neg};
+
+/// A point in affine coordinates. Arithmetic on affine points needs an
+/// inversion for every operation, prefer `ECJPoint` for longer chains.
 #[derive(Clone, Copy, Debug)]
 pub struct ECPoint {
-	pub x: BigNum,
-	pub y: BigNum,
+	pub x: FieldElement,
+	pub y: FieldElement,
 	pub inf: bool
 }
 
 pub const INF: ECPoint = ECPoint {
-	x: big_num::ZERO,
-	y: big_num::ZERO,
+	x: field::ZERO,
+	y: field::ZERO,
 	inf: true
 };
 
+/// Largest window accepted by `ECPoint::mul_wnaf`
+pub const MAX_WINDOW: u8 = 6;
+
+/// Window used by `mul_glv` and `jmul_add`
+pub const WINDOW: u8 = 4;
+
+/// Number of odd multiples in a table for `WINDOW`
+pub const TABLE_LEN: usize = 1 << (WINDOW - 1);
+
+/// The generator point
+pub const G: ECPoint = ECPoint {
+	x: field::GX,
+	y: field::GY,
+	inf: false
+};
+
+impl PartialEq for ECPoint {
+	fn eq(&self, other: &ECPoint) -> bool {
+		if self.inf || other.inf {
+			return self.inf == other.inf;
+		}
+
+		self.x == other.x && self.y == other.y
+	}
+}
+
+impl Eq for ECPoint {}
+
 impl<'a> Add<&'a ECPoint> for ECPoint {
 	type Output = ECPoint;
 
@@ -50,20 +95,51 @@ impl<'a> AddAssign<&'a ECPoint> for ECPoint {
 		// s = (y - yp) / (x - xp)
 		// nx = s**2 - x - xp
 		// ny = s * (x - nx) - y
-		let mut s = self.y.red_sub(&rhs.y);
+		let s = (self.y - &rhs.y) * &(self.x - &rhs.x).inv_vartime();
+
+		let nx = s.sqr() - &self.x - &rhs.x;
+		self.y = s * &(self.x - &nx) - &self.y;
+		self.x = nx;
+	}
+}
+
+impl Sub<&ECPoint> for ECPoint {
+	type Output = ECPoint;
+
+	fn sub(mut self, rhs: &ECPoint) -> ECPoint {
+		self.sub_assign(rhs);
+		self
+	}
+}
+
+impl SubAssign<&ECPoint> for ECPoint {
+	fn sub_assign(&mut self, rhs: &ECPoint) {
+		self.add_assign(&-*rhs);
+	}
+}
 
-		if s != 0 {
-			s.red_mul_mut(&self.x.red_sub(&rhs.x).red_invm())
+impl Neg for ECPoint {
+	type Output = ECPoint;
+
+	fn neg(self) -> ECPoint {
+		if self.inf {
+			self
+		} else {
+			ECPoint::new(self.x, -self.y)
 		}
+	}
+}
 
-		let nx = s.red_sqr().red_sub(&self.x).red_sub(&rhs.x);
-		self.y = s.red_mul(&self.x.red_sub(&nx)).red_sub(&self.y);
-		self.x = nx;
+impl Mul<&Scalar> for ECPoint {
+	type Output = ECJPoint;
+
+	fn mul(self, k: &Scalar) -> ECJPoint {
+		self.mul_ct(k)
 	}
 }
 
 impl ECPoint {
-	pub fn new(x: BigNum, y: BigNum) -> Self {
+	pub fn new(x: FieldElement, y: FieldElement) -> Self {
 		ECPoint {
 			x: x,
 			y: y,
@@ -71,43 +147,397 @@ impl ECPoint {
 		}
 	}
 
+	/// Decompress a point from its `x` coordinate and the parity of `y`,
+	/// returns `None` if `x^3 + 7` has no square root.
+	pub fn from_x(x: FieldElement, odd: bool) -> Option<Self> {
+		let y2 = x.sqr() * &x + &field::B;
+		let mut y = y2.sqrt()?;
+
+		if y.is_odd() != odd {
+			y = -y;
+		}
+
+		Some(ECPoint::new(x, y))
+	}
+
+	/// Whether the point satisfies `y^2 = x^3 + 7`
+	pub fn is_on_curve(&self) -> bool {
+		self.y.sqr() == self.x.sqr() * &self.x + &field::B
+	}
+
+	/// Whether the point is usable as a public key: on the curve and not
+	/// infinity. Coordinates are always below `P` as field elements.
+	pub fn is_valid(&self) -> bool {
+		!self.inf && self.is_on_curve()
+	}
+
+	/// Parse a serialized public key, see `PublicKey::parse`.
+	pub fn from_public_key(bytes: &[u8]) -> Result<ECPoint, Error> {
+		PublicKey::parse(bytes).map(ECPoint::from)
+	}
+
 	pub fn to_public_key(&self) -> [u8; 65] {
 		let mut public_key = [0u8; 65];
 
 		public_key[0] = 0x04;
 
-		self.x.write_bytes_to(&mut public_key[1..33]);
-		self.y.write_bytes_to(&mut public_key[33..65]);
+		public_key[1..33].copy_from_slice(&self.x.to_bytes());
+		public_key[33..65].copy_from_slice(&self.y.to_bytes());
 
 		return public_key;
 	}
 
+	pub fn to_public_key_compressed(self) -> [u8; 33] {
+		let mut public_key = [0u8; 33];
+
+		public_key[0] = 0x02 | self.y.is_odd() as u8;
+
+		public_key[1..33].copy_from_slice(&self.x.to_bytes());
+
+		public_key
+	}
+
 	pub fn double(&mut self) {
 		if self.inf {
 			return;
 		}
 
-		let yy = self.y.red_add(&self.y);
+		let yy = self.y + &self.y;
 
-		if yy == 0 {
+		if yy.is_zero() {
 			self.inf = true;
 			return;
 		}
 
-		let xsqr = self.x.red_sqr();
-		let s = xsqr.red_add(&xsqr).red_add(&xsqr).red_mul(&yy.red_invm());
+		let xsqr = self.x.sqr();
+		let s = (xsqr + &xsqr + &xsqr) * &yy.inv_vartime();
 
-		let nx = s.red_sqr().red_sub(&self.x.red_add(&self.x));
-		self.y = s.red_mul(&self.x.red_sub(&nx)).red_sub(&self.y);
+		let nx = s.sqr() - &(self.x + &self.x);
+		self.y = s * &(self.x - &nx) - &self.y;
 		self.x = nx;
 	}
 
 
-	pub fn neg(&self) -> ECPoint {
+	/// Computes `k1 * self + k2 * p2`, splitting both scalars with the
+	/// GLV endomorphism and sharing the doublings between all four halves.
+	/// The scalars are treated as public, the running time depends on
+	/// their values.
+	pub fn jmul_add(&self, k1: &Scalar, p2: &ECPoint, k2: &Scalar) -> ECJPoint {
+		let [a, b] = self.split_glv(k1);
+		let [c, d] = p2.split_glv(k2);
+
+		mul_joint(&[a, b, c, d])
+	}
+
+	/// Computes `k * self` using the GLV endomorphism, which halves the
+	/// number of doublings. The running time depends on `k`, which is
+	/// treated as public.
+	pub fn mul_glv(&self, k: &Scalar) -> ECJPoint {
+		mul_joint(&self.split_glv(k))
+	}
+
+	/// Computes `k * self` in constant time with respect to `k`, which
+	/// can be secret. The signed 4 bits digits of `k` pick points from a
+	/// table of `self, 2 * self, ..., 8 * self` without branching, as
+	/// `ECPointG::mul` does. Building the table still depends on `self`.
+	pub fn mul_ct(&self, k: &Scalar) -> ECJPoint {
 		if self.inf {
-			*self
-		} else {
-			ECPoint::new(self.x, self.y.red_neg())
+			return ECJPoint::default();
 		}
+
+		let mut jtable = [ECJPoint::from(*self); Window4::COLS];
+
+		for i in 1..Window4::COLS {
+			jtable[i] = jtable[i - 1] + self;
+		}
+
+		let mut table = [INF; Window4::COLS];
+		ECJPoint::batch_normalize(&jtable, &mut table);
+
+		// Same as `ECPointG::mul`, multiply by `N - k` for `k` above 2^255
+		// and negate the result.
+		let mut k = *k;
+		let high = k.is_high();
+		let mut neg_k = -k;
+		k.cmov(&neg_k, high);
+		neg_k.wipe();
+
+		let mut digits = [(0u32, 0u32); ec_point_g::MAX_DIGITS];
+		ec_point_g::recode::<Window4>(&k, &mut digits);
+		k.wipe();
+
+		let mut acc = ECJPoint::default();
+
+		for &(abs, negative) in digits[..Window4::ROWS * Window4::SPACING].iter().rev() {
+			for _ in 0..Window4::BITS {
+				acc.double_ct();
+			}
+
+			let mut point = table[0];
+
+			for (j, p) in table.iter().enumerate() {
+				point.cmov(p, words::eq(abs, j as u32 + 1) == 1);
+			}
+
+			let neg = -point.y;
+			point.y.cmov(&neg, negative == 1);
+
+			// `acc` is a multiple of 16 times `self` below N, and `point`
+			// at most 8 times it, so they can only collide at infinity,
+			// which `mixed_add_ct` handles.
+			let mut sum = acc;
+			sum.mixed_add_ct(&point);
+			acc.cmov(&sum, abs != 0);
+		}
+
+		words::wipe(&mut digits);
+
+		let neg = -acc.y;
+		acc.y.cmov(&neg, high);
+
+		acc
+	}
+
+	/// `(BETA * x, y)`, which is equal to `LAMBDA * self`
+	pub fn endomorphism(&self) -> ECPoint {
+		if self.inf {
+			return *self;
+		}
+
+		ECPoint::new(self.x * &field::BETA, self.y)
+	}
+
+	/// Split `k * self` into `k1 * p1 + k2 * p2` with `k1` and `k2` at
+	/// most 128 bits long.
+	pub(crate) fn split_glv(&self, k: &Scalar) -> [(ECPoint, Scalar); 2] {
+		let (mut k1, mut k2) = k.split_lambda();
+		let mut p1 = *self;
+		let mut p2 = self.endomorphism();
+
+		if k1.is_high() {
+			k1 = -k1;
+			p1 = -p1;
+		}
+
+		if k2.is_high() {
+			k2 = -k2;
+			p2 = -p2;
+		}
+
+		[(p1, k1), (p2, k2)]
+	}
+
+	/// Computes `k * self` with a width `w + 1` NAF of `k`, using a table
+	/// of the odd multiples `self, 3 * self, ..., (2^w - 1) * self` built
+	/// for this call. `w` is clamped to between 1 and `MAX_WINDOW`. The
+	/// running time depends on `k`, which is treated as public.
+	pub fn mul_wnaf(&self, k: &Scalar, w: u8) -> ECJPoint {
+		let w = w.clamp(1, MAX_WINDOW);
+
+		let naf = BigNum::from(k).get_naf(w);
+
+		let mut table = [ECJPoint::default(); 1 << (MAX_WINDOW - 1)];
+		odd_multiples(self, &mut table[..1 << (w - 1)]);
+
+		let mut acc = ECJPoint::default();
+
+		for &digit in naf.as_slice().iter().rev() {
+			acc.double();
+
+			if digit > 0 {
+				acc += &table[(digit / 2) as usize];
+			} else if digit < 0 {
+				acc -= &table[(-digit / 2) as usize];
+			}
+		}
+
+		acc
+	}
+
+	/// Copy `src` into `self` if `flag` is set, without branching on it.
+	#[inline]
+	pub fn cmov(&mut self, src: &ECPoint, flag: bool) {
+		self.x.cmov(&src.x, flag);
+		self.y.cmov(&src.y, flag);
+		self.inf = (self.inf & !flag) | (src.inf & flag);
+	}
+}
+
+/// Fill `table` with `p, 3 * p, 5 * p, ...`
+fn odd_multiples(p: &ECPoint, table: &mut [ECJPoint]) {
+	let mut double = ECJPoint::from(*p);
+	double.double();
+
+	table[0] = ECJPoint::from(*p);
+
+	for i in 1..table.len() {
+		table[i] = table[i - 1] + &double;
+	}
+}
+
+/// Sum of `k * p` over up to 4 `terms`, see `interleaved_wnaf`.
+fn mul_joint(terms: &[(ECPoint, Scalar)]) -> ECJPoint {
+	assert!(terms.len() <= 4);
+
+	let mut nafs = [NAF::new(), NAF::new(), NAF::new(), NAF::new()];
+	let mut tables = [[ECJPoint::default(); TABLE_LEN]; 4];
+
+	for (&(p, k), (naf, table)) in terms.iter().zip(nafs.iter_mut().zip(tables.iter_mut())) {
+		wnaf_term(&p, &k, naf, table);
+	}
+
+	interleaved_wnaf(&nafs[..terms.len()], &tables[..terms.len()])
+}
+
+/// Prepare the wNAF of `k` and the odd multiples of `p` for `interleaved_wnaf`
+pub(crate) fn wnaf_term(p: &ECPoint, k: &Scalar, naf: &mut NAF, table: &mut [ECJPoint; TABLE_LEN]) {
+	*naf = BigNum::from(k).get_naf(WINDOW);
+
+	odd_multiples(p, table);
+}
+
+/// Sum of `k * p` over all terms prepared with `wnaf_term`, walking the
+/// wNAFs of width `WINDOW + 1` together so that the doublings are shared
+/// (Strauss' method).
+pub(crate) fn interleaved_wnaf(nafs: &[NAF], tables: &[[ECJPoint; TABLE_LEN]]) -> ECJPoint {
+	let len = nafs.iter().map(|naf| naf.as_slice().len()).max().unwrap_or(0);
+
+	let mut acc = ECJPoint::default();
+
+	for i in (0..len).rev() {
+		acc.double();
+
+		for (naf, table) in nafs.iter().zip(tables.iter()) {
+			match naf.as_slice().get(i) {
+				Some(&digit) if digit > 0 => acc += &table[(digit / 2) as usize],
+				Some(&digit) if digit < 0 => acc -= &table[(-digit / 2) as usize],
+				_ => {}
+			}
+		}
+	}
+
+	acc
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use scalar;
+	use ecj_point;
+	use ec_point_g::ECPointG;
+
+	#[test]
+	fn ec_point_is_valid() {
+		assert!(G.is_valid());
+		assert!((-G).is_valid());
+		assert!(!INF.is_valid());
+
+		let mut off_curve = G;
+		off_curve.y += &field::ONE;
+
+		assert!(!off_curve.is_on_curve());
+		assert!(!off_curve.is_valid());
+
+		// (0, sqrt(7)) doesn't exist, but (1, y) does
+		assert_eq!(ECPoint::from_x(field::ZERO, false), None);
+		assert!(ECPoint::from_x(field::ONE, false).unwrap().is_valid());
+	}
+
+	#[test]
+	fn ec_point_ops() {
+		let g2 = G + &G;
+		let g3 = G * &Scalar::from(3);
+
+		assert_eq!(G * &Scalar::from(2), g2);
+		assert_eq!(ECPoint::from(g3) - &G, g2);
+		assert_eq!(g3 - &G - &g2, ecj_point::INF);
+		assert_eq!(G - &G, INF);
+		assert_eq!(G + &INF, G);
+		assert_eq!(INF + &G, G);
+		assert_eq!(-INF, INF);
+
+		// (N - 1) * G = -G
+		assert_eq!(G * &-scalar::ONE, -G);
+		assert_eq!(INF * &Scalar::from(5), ecj_point::INF);
+		assert_eq!(G * &scalar::ZERO, ecj_point::INF);
+	}
+
+	#[test]
+	fn ec_point_mul_wnaf() {
+		let k = Scalar::from_bytes_reduced(&[0xa5; 32]);
+		let expected = G.mul_wnaf(&k, 1);
+
+		for w in 2..=MAX_WINDOW {
+			assert_eq!(G.mul_wnaf(&k, w), expected);
+			assert_eq!(G.mul_wnaf(&-k, w), -expected);
+			assert_eq!(G.mul_wnaf(&scalar::ONE, w), G);
+		}
+
+		// out of range windows are clamped
+		assert_eq!(G.mul_wnaf(&k, 0), expected);
+		assert_eq!(G.mul_wnaf(&k, MAX_WINDOW + 1), expected);
+		assert_eq!(G.mul_wnaf(&k, u8::MAX), expected);
+
+		let g = ECPointG::new();
+
+		assert_eq!(expected, g.mul(&k));
+	}
+
+	#[test]
+	fn ec_point_mul_glv() {
+		assert_eq!(G.mul_wnaf(&scalar::LAMBDA, WINDOW), G.endomorphism());
+
+		let g = ECPointG::new();
+		let mut k = Scalar::from_bytes_reduced(&[0x5a; 32]);
+
+		for _ in 0..8 {
+			let expected = g.mul(&k);
+
+			assert_eq!(G.mul_glv(&k), expected);
+			assert_eq!(G.jmul_add(&k, &G, &-scalar::ONE), expected - &G);
+
+			k = k * &k + &scalar::ONE;
+		}
+	}
+
+	#[test]
+	fn ec_point_mul_ct() {
+		let p = ECPoint::from(G.mul_glv(&Scalar::from(7)));
+		let mut k = Scalar::from_bytes_reduced(&[0xc3; 32]);
+
+		let scalars = [
+			scalar::ZERO,
+			scalar::ONE,
+			Scalar::from(8),
+			Scalar::from(9),
+			Scalar::from(16),
+			-scalar::ONE,
+			-Scalar::from(8),
+			Scalar::from_bytes_reduced(&[0xff; 32]),
+			Scalar::from_bytes_reduced(&[0x88; 32])
+		];
+
+		for k in scalars.iter() {
+			assert_eq!(p.mul_ct(k), p.mul_glv(k));
+		}
+
+		for _ in 0..8 {
+			assert_eq!(p.mul_ct(&k), p.mul_glv(&k));
+			assert_eq!(G.mul_ct(&k), G.mul_glv(&k));
+
+			k = k * &k + &scalar::ONE;
+		}
+
+		assert_eq!(INF.mul_ct(&k), ecj_point::INF);
+	}
+
+	#[test]
+	fn ec_point_bytes() {
+		let key = G.to_public_key();
+
+		assert_eq!(ECPoint::from_public_key(&key), Ok(G));
+		assert_eq!(ECPoint::from_public_key(&G.to_public_key_compressed()), Ok(G));
+		assert_eq!(ECPoint::from_public_key(&(-G).to_public_key_compressed()), Ok(-G));
+		assert_eq!(ECPoint::from_public_key(&INF.to_public_key()), Err(Error::InvalidPublicKey));
 	}
 }
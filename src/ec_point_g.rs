@@ -1,92 +1,534 @@
-use big_num::BigNum;
-use ecj_point::ECJPoint;
-use ec_point::ECPoint;
+// Style lints the original code predates
+#![allow(clippy::new_without_default)]
 
-pub struct ECPointG {
-	points: [ECPoint; 66],
-	negpoints: [ECPoint; 66]
+use scalar::{self, Scalar};
+use field::FieldElement;
+use ecj_point::{self, ECJPoint};
+use ec_point::{self, ECPoint};
+use rfc6979::Rfc6979;
+use words;
+use core::marker::PhantomData;
+use core::mem::{self, MaybeUninit};
+use core::ptr;
+use core::slice::{self, Chunks};
+
+/// Window size of the `ECPointG` table. Generator multiplication adds
+/// one point for every `BITS` bits of the scalar, picked from a row of
+/// `2^(BITS - 1)` precomputed points and negated for negative digits.
+/// Each row serves `SPACING` consecutive digits, with `BITS` doublings
+/// in between, so larger windows and a smaller spacing trade memory for
+/// fewer operations.
+///
+/// # Safety
+///
+/// `BITS` must be at least 2 and `ROWS * SPACING` at most `MAX_DIGITS`,
+/// and `Table` must be an array of exactly `ROWS * COLS` points, as it's
+/// filled in place through a pointer by `ECPointG::init`.
+pub unsafe trait Window {
+	/// Bits of the scalar covered by a digit
+	const BITS: usize;
+
+	/// Digits sharing a row
+	const SPACING: usize = 1;
+
+	/// Points in a row
+	const COLS: usize = 1 << (Self::BITS - 1);
+
+	/// Rows needed to cover 256 bits
+	const ROWS: usize = 256usize.div_ceil(Self::BITS * Self::SPACING);
+
+	/// Storage for `ROWS * COLS` points
+	type Table: AsRef<[TablePoint]>;
 }
 
+/// Most signed digits a scalar can be recoded into, see `Window`
+pub const MAX_DIGITS: usize = 128;
+
+/// An affine point of the table, as the little-endian words of its
+/// coordinates. The table never holds infinity, and points are negated
+/// on the fly, so this is all there is to store.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct TablePoint {
+	x: [u32; 8],
+	y: [u32; 8]
+}
+
+/// 2 bits windows spaced by 8: 32 points, 2 KB
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Window2;
+
+/// 4 bits windows spaced by 4: 128 points, 8 KB
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Window4;
+
+/// 8 bits windows: 4096 points, 256 KB
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Window8;
+
+unsafe impl Window for Window2 {
+	const BITS: usize = 2;
+	const SPACING: usize = 8;
+
+	type Table = [TablePoint; 32];
+}
+
+unsafe impl Window for Window4 {
+	const BITS: usize = 4;
+	const SPACING: usize = 4;
+
+	type Table = [TablePoint; 128];
+}
+
+unsafe impl Window for Window8 {
+	const BITS: usize = 8;
+
+	type Table = [TablePoint; 4096];
+}
+
+impl TablePoint {
+	fn from_point(point: &ECPoint) -> Self {
+		TablePoint {
+			x: point.x.to_words(),
+			y: point.y.to_words()
+		}
+	}
+
+	fn to_point(self) -> ECPoint {
+		ECPoint::new(FieldElement::from_words(self.x), FieldElement::from_words(self.y))
+	}
+
+	/// Copy `src` into `self` if `flag` is set, without branching on it.
+	#[inline]
+	fn cmov(&mut self, src: &TablePoint, flag: bool) {
+		words::select(&mut self.x, &src.x, flag as u32);
+		words::select(&mut self.y, &src.y, flag as u32);
+	}
+}
+
+/// The window of the table embedded by the `static-table` features
+#[cfg(static_table = "w2")]
+pub type GlobalWindow = Window2;
+
+#[cfg(static_table = "w4")]
+pub type GlobalWindow = Window4;
+
+#[cfg(static_table = "w8")]
+pub type GlobalWindow = Window8;
+
+pub struct ECPointG<W: Window = Window4> {
+	// table[i * W::COLS + j] = (j + 1) * 2^(W::BITS * W::SPACING * i) * G
+	table: W::Table,
+	blinding: Blinding,
+	window: PhantomData<W>
+}
+
+/// Offset `b` applied to the scalars by `ECPointG::mul`, which computes
+/// `k * G` as `(k + b) * G - b * G`.
+#[derive(Clone, Copy)]
+struct Blinding {
+	scalar: Scalar,
+	// -b * G, with a random Z coordinate, divided by the 2^(BITS * (SPACING - 1))
+	// it's multiplied by through the doublings of `mul`
+	point: ECJPoint
+}
+
+const NO_BLINDING: Blinding = Blinding {
+	scalar: scalar::ZERO,
+	point: ecj_point::INF
+};
+
+/// Built by the build script with the `static-table` features
+#[cfg(static_table)]
+static GLOBAL: ECPointG<GlobalWindow> = ECPointG {
+	table: include!(concat!(env!("OUT_DIR"), "/ecpoint_g.rs")),
+	blinding: NO_BLINDING,
+	window: PhantomData
+};
+
 impl ECPointG {
 	/// This can be very expensive and should  be performed only once.
 	/// Ideally this would be memoized (using lazy_static or otherwise),
-	/// however no_std makes that difficult, and using static would
-	/// bloat up the WASM binary size.
+	/// however no_std makes that difficult. Alternatively the
+	/// `static-table` features embed the table in the binary, see
+	/// `ECPointG::global`.
 	pub fn new() -> Self {
-		let x: &[u8] = &[
-			0x79,0xBE,0x66,0x7E,0xF9,0xDC,0xBB,0xAC,0x55,0xA0,0x62,0x95,0xCE,
-			0x87,0x0B,0x07,0x02,0x9B,0xFC,0xDB,0x2D,0xCE,0x28,0xD9,0x59,0xF2,
-			0x81,0x5B,0x16,0xF8,0x17,0x98
-		];
-		let y: &[u8] = &[
-			0x48,0x3A,0xDA,0x77,0x26,0xA3,0xC4,0x65,0x5D,0xA4,0xFB,0xFC,0x0E,
-			0x11,0x08,0xA8,0xFD,0x17,0xB4,0x48,0xA6,0x85,0x54,0x19,0x9C,0x47,
-			0xD0,0x8F,0xFB,0x10,0xD4,0xB8
-		];
+		ECPointG::with_window(Window4)
+	}
+}
+
+#[cfg(static_table)]
+impl ECPointG<GlobalWindow> {
+	/// The table precomputed at build time, which costs nothing at runtime
+	/// but adds the size of the table to the binary. The window is picked
+	/// with the `static-table-small` (`Window2`), `static-table` (`Window4`)
+	/// or `static-table-large` (`Window8`) feature, the largest one wins.
+	/// Being immutable, this table can't be blinded with `randomize`.
+	pub fn global() -> &'static Self {
+		&GLOBAL
+	}
+}
+
+impl<W: Window> ECPointG<W> {
+	/// Like `ECPointG::new`, with a table for the given window size. The
+	/// table is returned by value, use `ECPointG::init` to keep it off
+	/// the stack.
+	pub fn with_window(_window: W) -> Self {
+		let mut slot = MaybeUninit::uninit();
 
-		let mut acc = ECPoint::new(x.into(), y.into());
+		ECPointG::init(&mut slot);
+
+		// Safe since `init` has filled the whole table
+		unsafe { slot.assume_init() }
+	}
 
-		// dstep = 4
-		// points.len = 1 + (257 / dstep) = 66
+	/// Build the table directly in `slot`, which can be placed in static
+	/// or heap memory so that the table never has to fit on the stack.
+	/// Returns a reference to the now initialized `slot`.
+	pub fn init(slot: &mut MaybeUninit<Self>) -> &mut Self {
+		let len = W::ROWS * W::COLS;
 
-		let mut res = ECPointG {
-			points: [acc; 66],
-			negpoints: [acc.neg(); 66]
+		assert_eq!(mem::size_of::<W::Table>(), len * mem::size_of::<TablePoint>());
+
+		// Safe since `Window` guarantees that the table is an array of
+		// `len` points, and `window` is zero sized.
+		let table = unsafe {
+			ptr::addr_of_mut!((*slot.as_mut_ptr()).blinding).write(NO_BLINDING);
+
+			let table = ptr::addr_of_mut!((*slot.as_mut_ptr()).table);
+
+			slice::from_raw_parts_mut(table as *mut MaybeUninit<TablePoint>, len)
 		};
 
-		for (point, negpoint) in res.points[1..]
-			.iter_mut()
-			.zip(res.negpoints[1..].iter_mut())
-		{
-			// dstep doubles
-			acc.double();
-			acc.double();
-			acc.double();
-			acc.double();
-			*point = acc;
-			*negpoint = acc.neg();
+		// The bases 2^(BITS * SPACING * i) * G of the rows are doubled in
+		// Jacobian coordinates, and converted to affine a batch at a time
+		// sharing a single inversion.
+		let mut base = ECJPoint::from(ec_point::G);
+		let mut batch = [base; 16];
+		let mut affine = [ec_point::G; 16];
+
+		for rows in table.chunks_mut(16 * W::COLS) {
+			let n = rows.len() / W::COLS;
+
+			for point in batch[..n].iter_mut() {
+				*point = base;
+
+				for _ in 0..W::BITS * W::SPACING {
+					base.double();
+				}
+			}
+
+			ECJPoint::batch_normalize(&batch[..n], &mut affine[..n]);
+
+			for (row, base) in rows.chunks_mut(W::COLS).zip(affine.iter()) {
+				let mut point = *base;
+
+				row[0] = MaybeUninit::new(TablePoint::from_point(&point));
+
+				for entry in row[1..].iter_mut() {
+					point += base;
+					*entry = MaybeUninit::new(TablePoint::from_point(&point));
+				}
+			}
+		}
+
+		// Safe since every point of the table has been written
+		unsafe { slot.assume_init_mut() }
+	}
+
+	/// Blind all the following `mul` calls with a secret offset and a
+	/// random Z coordinate derived from `seed`, so that the intermediate
+	/// values can't be predicted from `k` by power or EM analysis. The
+	/// new blinding also depends on the previous one, so calling this
+	/// again with fresh entropy only adds to it.
+	pub fn randomize(&mut self, seed: &[u8; 32]) {
+		let mut drbg = Rfc6979::new(&self.blinding.scalar.to_bytes(), seed);
+
+		let z = loop {
+			match FieldElement::from_bytes(&drbg.next_nonce()) {
+				Ok(z) if !z.is_zero() => break z,
+				_ => continue
+			}
+		};
+
+		// b = 0 would leave the blinding point at infinity without its
+		// z being zero
+		let b = loop {
+			match Scalar::from_bytes(&drbg.next_nonce()) {
+				Ok(b) if !b.is_zero() => break b,
+				_ => continue
+			}
+		};
+
+		// the doublings of `mul` multiply the starting point by 2^shift
+		let mut scale = scalar::ONE;
+
+		for _ in 0..W::BITS * (W::SPACING - 1) {
+			scale = scale + &scale;
 		}
 
-		res
+		let point = -self.mul_blinded(&(b * &scale.inv()), &NO_BLINDING);
+		let z2 = z.sqr();
+
+		self.blinding = Blinding {
+			scalar: b,
+			point: ECJPoint::new(point.x * &z2, point.y * &z2 * &z, z)
+		};
+	}
+
+	/// Multiply the generator by `k` in constant time: the same sequence
+	/// of operations and memory accesses is performed for every `k`, so
+	/// this is safe to use with secret scalars.
+	pub fn mul(&self, k: &Scalar) -> ECPoint {
+		self.mul_blinded(k, &self.blinding)
 	}
 
-	pub fn mul(&self, num: &mut BigNum) -> ECPoint {
-		let naf = num.get_naf(1);
+	fn mul_blinded(&self, k: &Scalar, blinding: &Blinding) -> ECPoint {
+		let mut k = *k + &blinding.scalar;
+		let mut acc = blinding.point;
+
+		// Recoding into signed digits in [-2^(BITS - 1), 2^(BITS - 1)]
+		// needs an extra carry digit for `k` above 2^255, so multiply by
+		// `N - k` instead and negate the result, starting from `b * G`.
+		let high = k.is_high();
+		let mut neg_k = -k;
+		k.cmov(&neg_k, high);
+		neg_k.wipe();
+
+		let neg = -acc.y;
+		acc.y.cmov(&neg, high);
+
+		let mut digits = [(0u32, 0u32); MAX_DIGITS];
+		recode::<W>(&k, &mut digits);
+		k.wipe();
+
+		// Digit `i * SPACING + s` is taken from row `i`, and doubled
+		// `BITS * s` times along with the rest of the accumulator.
+		for s in (0..W::SPACING).rev() {
+			if s != W::SPACING - 1 {
+				for _ in 0..W::BITS {
+					acc.double_ct();
+				}
+			}
+
+			for (i, row) in self.rows().enumerate() {
+				let (abs, negative) = digits[i * W::SPACING + s];
 
-		let mut repr = [0i8; 128]; // len = max naf len / 4
-		let mut rlen = 0;
+				let mut entry = row[0];
 
-		for c in naf.as_slice().chunks(4) {
-			// values in chunk are -1, 0 or 1
-			repr[rlen] = match c.len() {
-				4 => (c[3] << 3) + (c[2] << 2) + (c[1] << 1) + c[0],
-				3 => (c[2] << 2) + (c[1] << 1) + c[0],
-				2 => (c[1] << 1) + c[0],
-				1 => c[0],
-				_ => unreachable!()
-			};
-			rlen += 1;
+				for (j, p) in row.iter().enumerate() {
+					entry.cmov(p, words::eq(abs, j as u32 + 1) == 1);
+				}
+
+				let mut point = entry.to_point();
+				let neg = -point.y;
+				point.y.cmov(&neg, negative == 1);
+
+				// `mixed_add_ct` requires `acc` to differ from `point` and
+				// `-point`. Without blinding `acc` and `point` are sums of
+				// distinct signed digits, which are below N in absolute
+				// value and can't add up to zero, so they never collide.
+				// With a random offset this only happens with negligible
+				// probability.
+				let mut sum = acc;
+				sum.mixed_add_ct(&point);
+				acc.cmov(&sum, abs != 0);
+			}
 		}
 
-		let mut a = ECJPoint::default();
-		let mut b = ECJPoint::default();
+		words::wipe(&mut digits);
+
+		let mut point = ECPoint::from(acc);
+		let neg = -point.y;
+		point.y.cmov(&neg, high);
+
+		point
+	}
+
+	/// Variable time multiplication, only to be used with public scalars.
+	/// Uses the same table and recoding as `mul`, but only adds the
+	/// non-zero digits and indexes the rows directly.
+	pub fn mul_vartime(&self, k: &Scalar) -> ECPoint {
+		let high = k.is_high();
+		let k = if high { -*k } else { *k };
+
+		let mut digits = [(0u32, 0u32); MAX_DIGITS];
+		recode::<W>(&k, &mut digits);
 
-		// I = ((1 << (step + 1)) - (step % 2 === 0 ? 2 : 1)) / 3 == 10
-		let mut i = 10;
+		let mut acc = ECJPoint::default();
 
-		while i > 0 {
-			for (ri, rval) in repr[..rlen].iter().enumerate() {
-				if rval == &i {
-					b.mixed_add(&self.points[ri]);
-				} else if rval == &-i {
-					b.mixed_add(&self.negpoints[ri]);
+		for s in (0..W::SPACING).rev() {
+			if s != W::SPACING - 1 {
+				for _ in 0..W::BITS {
+					acc.double();
 				}
 			}
 
-			a += &b;
-			i -= 1;
+			for (i, row) in self.rows().enumerate() {
+				let (abs, negative) = digits[i * W::SPACING + s];
+
+				if abs == 0 {
+					continue;
+				}
+
+				let point = row[abs as usize - 1].to_point();
+
+				if negative == 1 {
+					acc -= &point;
+				} else {
+					acc += &point;
+				}
+			}
+		}
+
+		let point = acc.to_affine_vartime();
+
+		if high {
+			-point
+		} else {
+			point
+		}
+	}
+
+	#[inline]
+	fn rows(&self) -> Chunks<'_, TablePoint> {
+		let table: &[TablePoint] = self.table.as_ref();
+
+		table.chunks(W::COLS)
+	}
+}
+
+/// Recode a scalar below 2^255 into `ROWS * SPACING` signed digits of
+/// `BITS` bits, as pairs of the absolute value and 1 for negative digits.
+/// Runs in constant time.
+pub(crate) fn recode<W: Window>(k: &Scalar, digits: &mut [(u32, u32); MAX_DIGITS]) {
+	let half = W::COLS as u32;
+	let mut bytes = k.to_bytes();
+	let mut carry = 0u32;
+
+	for (i, digit) in digits[..W::ROWS * W::SPACING].iter_mut().enumerate() {
+		// d in [0, 2 * half], carry when d > half, the digit is then d - 2 * half
+		let d = words::read_bits(&bytes, i * W::BITS, W::BITS) as u32 + carry;
+		carry = half.wrapping_sub(d) >> 31;
+
+		*digit = (d ^ ((d ^ (2 * half).wrapping_sub(d)) & words::mask(carry)), carry);
+	}
+
+	words::wipe(&mut bytes);
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use scalar;
+	use table_gen;
+
+	fn check_mul<W: Window>(g: &ECPointG<W>) {
+		let k = Scalar::from_bytes_reduced(&[0x88; 32]);
+
+		let scalars = [
+			scalar::ONE,
+			Scalar::from(2),
+			Scalar::from(8),
+			Scalar::from(9),
+			Scalar::from(0x88888888),
+			-scalar::ONE,
+			-Scalar::from(0x77777777),
+			k,
+			-k
+		];
+
+		for k in scalars.iter() {
+			let expected = ECPoint::from(ec_point::G.mul_glv(k));
+
+			assert_eq!(g.mul(k), expected);
+			assert_eq!(g.mul_vartime(k), expected);
 		}
 
-		a.into()
+		assert!(g.mul(&scalar::ZERO).inf);
+		assert!(g.mul_vartime(&scalar::ZERO).inf);
+	}
+
+	#[test]
+	fn mul_matches_vartime() {
+		check_mul(&ECPointG::new());
+	}
+
+	#[test]
+	fn mul_windows() {
+		check_mul(&ECPointG::with_window(Window2));
+		check_mul(&ECPointG::with_window(Window8));
+	}
+
+	#[test]
+	fn mul_randomized() {
+		let mut g = ECPointG::with_window(Window2);
+
+		g.randomize(&[0x42; 32]);
+		check_mul(&g);
+
+		assert!(!g.blinding.scalar.is_zero());
+		assert_eq!(g.mul(&-g.blinding.scalar), ECPoint::from(ec_point::G.mul_glv(&-g.blinding.scalar)));
+
+		g.randomize(&[0x00; 32]);
+		check_mul(&g);
+	}
+
+	#[test]
+	fn mul_randomized_spacing() {
+		let mut g = ECPointG::new();
+
+		g.randomize(&[0x17; 32]);
+		check_mul(&g);
+	}
+
+	#[test]
+	fn table_sizes() {
+		assert_eq!(mem::size_of::<TablePoint>(), 64);
+		assert_eq!(mem::size_of::<<Window2 as Window>::Table>(), 2 * 1024);
+		assert_eq!(mem::size_of::<<Window4 as Window>::Table>(), 8 * 1024);
+		assert_eq!(mem::size_of::<<Window8 as Window>::Table>(), 256 * 1024);
+
+		// at most half of the 22 KB that `points` and `negpoints` took
+		assert!(mem::size_of::<ECPointG>() <= 11 * 1024);
+	}
+
+	#[test]
+	fn init_in_place() {
+		let mut slot = MaybeUninit::uninit();
+		let g: &mut ECPointG<Window2> = ECPointG::init(&mut slot);
+
+		assert!(g.rows().eq(ECPointG::with_window(Window2).rows()));
+		check_mul(g);
+	}
+
+	fn check_table_gen<W: Window>(g: &ECPointG<W>) {
+		let table: &[TablePoint] = g.table.as_ref();
+		let mut points = table.iter();
+		let &(_, bits, spacing) = table_gen::WINDOWS
+			.iter()
+			.find(|&&(_, bits, _)| bits == W::BITS)
+			.unwrap();
+
+		assert_eq!(spacing, W::SPACING);
+
+		table_gen::table_points(bits, spacing, |x, y| {
+			assert_eq!(points.next(), Some(&TablePoint { x: *x, y: *y }));
+		});
+
+		assert_eq!(points.next(), None);
+	}
+
+	#[test]
+	fn table_gen_matches_init() {
+		check_table_gen(&ECPointG::with_window(Window2));
+		check_table_gen(&ECPointG::new());
+	}
+
+	#[cfg(static_table)]
+	#[test]
+	fn global_matches_new() {
+		let g = ECPointG::with_window(GlobalWindow::default());
+
+		assert!(g.rows().eq(ECPointG::global().rows()));
 	}
 }
@@ -0,0 +1,413 @@
+use big_num::{self, BigNum};
+use scalar::Scalar;
+use field::{self, FieldElement};
+use ec_point::{self, ECPoint};
+use ec_point_g::{ECPointG, Window};
+use public_key::PublicKey;
+use rfc6979::Rfc6979;
+use error::Error;
+use secret_key::SecretKey;
+use words;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Signature {
+	pub r: Scalar,
+	pub s: Scalar
+}
+
+impl Signature {
+	/// Parse a compact 64 bytes signature, fails with `InvalidSignature`
+	/// if either `r` or `s` overflows `N`.
+	pub fn from_bytes(bytes: &[u8; 64]) -> Result<Signature, Error> {
+		let mut r = [0u8; 32];
+		let mut s = [0u8; 32];
+
+		r.copy_from_slice(&bytes[0..32]);
+		s.copy_from_slice(&bytes[32..64]);
+
+		match (Scalar::from_bytes(&r), Scalar::from_bytes(&s)) {
+			(Ok(r), Ok(s)) => Ok(Signature { r, s }),
+			_ => Err(Error::InvalidSignature)
+		}
+	}
+
+	/// Compact 64 bytes serialization, `r` followed by `s`
+	pub fn to_bytes(self) -> [u8; 64] {
+		let mut bytes = [0u8; 64];
+
+		bytes[0..32].copy_from_slice(&self.r.to_bytes());
+		bytes[32..64].copy_from_slice(&self.s.to_bytes());
+
+		bytes
+	}
+}
+
+/// Sign a 32 bytes message hash, deriving the nonce deterministically
+/// as per RFC 6979. The resulting `s` is always normalized to the lower
+/// half of the group order. Along with the signature the recovery id
+/// (`0..=3`) is returned, which can be passed to `recover`.
+pub fn sign<W: Window>(g: &ECPointG<W>, msg_hash: &[u8; 32], secret: &[u8]) -> Result<(Signature, u8), Error> {
+	let secret = SecretKey::parse(secret)?;
+	let z = Scalar::from_bytes_reduced(msg_hash);
+
+	let mut secret_bytes = secret.to_bytes();
+	let mut drbg = Rfc6979::new(&secret_bytes, &z.to_bytes());
+
+	words::wipe(&mut secret_bytes);
+
+	loop {
+		let mut nonce = drbg.next_nonce();
+		let k = Scalar::from_bytes(&nonce);
+
+		words::wipe(&mut nonce);
+
+		let mut k = match k {
+			Ok(k) if !k.is_zero() => k,
+			_ => continue
+		};
+
+		let res = sign_with_nonce(g, &z, secret.scalar(), &k);
+
+		k.wipe();
+
+		if let Some(res) = res {
+			return Ok(res);
+		}
+	}
+}
+
+/// A single signing attempt, `None` if either `r` or `s` turns out zero.
+fn sign_with_nonce<W: Window>(g: &ECPointG<W>, z: &Scalar, d: &Scalar, k: &Scalar) -> Option<(Signature, u8)> {
+	let point = g.mul(k);
+
+	// bit 0: parity of y, bit 1: x overflowing N
+	let mut recid = point.y.is_odd() as u8;
+
+	let x = point.x.to_bytes();
+
+	if Scalar::from_bytes(&x).is_err() {
+		recid |= 2;
+	}
+
+	let r = Scalar::from_bytes_reduced(&x);
+
+	if r.is_zero() {
+		return None;
+	}
+
+	// s = k^-1 * (z + r * d)
+	let mut kinv = k.inv();
+	let mut s = kinv * &(*z + &(r * d));
+
+	kinv.wipe();
+
+	if s.is_zero() {
+		return None;
+	}
+
+	if s.is_high() {
+		s = -s;
+		recid ^= 1;
+	}
+
+	Some((Signature { r, s }, recid))
+}
+
+/// Verify a signature of a 32 bytes message hash against a serialized
+/// public key, in any of the forms accepted by `PublicKey::parse`. Just
+/// like libsecp256k1, only signatures with `s` in the lower half of the
+/// group order are accepted. Fails with `InvalidSignature` if the
+/// signature doesn't match, or with the error of parsing `public_key`.
+pub fn verify(msg_hash: &[u8; 32], signature: &Signature, public_key: &[u8]) -> Result<(), Error> {
+	let q = *PublicKey::parse(public_key)?.point();
+
+	let Signature { r, s } = *signature;
+
+	if r.is_zero() || s.is_zero() || s.is_high() {
+		return Err(Error::InvalidSignature);
+	}
+
+	let z = Scalar::from_bytes_reduced(msg_hash);
+
+	// u1 = z / s, u2 = r / s
+	let w = s.inv_vartime();
+	let u1 = z * &w;
+	let u2 = r * &w;
+
+	let point = ec_point::G.jmul_add(&u1, &q, &u2);
+
+	if point.inf() || !point.eq_x_to_p(&r) {
+		return Err(Error::InvalidSignature);
+	}
+
+	Ok(())
+}
+
+/// Recover the 65 bytes uncompressed public key from a 64 bytes compact
+/// signature and the recovery id produced by `sign`.
+pub fn recover(msg_hash: &[u8; 32], sig64: &[u8; 64], recid: u8) -> Result<[u8; 65], Error> {
+	if recid > 3 {
+		return Err(Error::InvalidRecoveryId);
+	}
+
+	let Signature { r, s } = Signature::from_bytes(sig64)?;
+
+	if r.is_zero() || s.is_zero() {
+		return Err(Error::InvalidSignature);
+	}
+
+	// Lift `r` back to the x coordinate of the nonce point. If the x
+	// coordinate overflowed `N` during signing, it was `r + N`, which
+	// is only possible if that is still below `P`.
+	let mut x = FieldElement::from_bytes_reduced(&r.to_bytes());
+
+	if recid & 2 != 0 {
+		if &BigNum::from(&r) >= big_num::PSN {
+			return Err(Error::InvalidSignature);
+		}
+
+		x += &field::ORDER;
+	}
+
+	let nonce_point = ECPoint::from_x(x, recid & 1 == 1).ok_or(Error::InvalidSignature)?;
+
+	let z = Scalar::from_bytes_reduced(msg_hash);
+
+	// Q = r^-1 * (s * R - z * G)
+	let rinv = r.inv_vartime();
+	let u1 = s * &rinv;
+	let u2 = -z * &rinv;
+
+	let point = nonce_point.jmul_add(&u1, &ec_point::G, &u2);
+
+	if point.inf() {
+		return Err(Error::InvalidSignature);
+	}
+
+	Ok(point.to_affine_vartime().to_public_key())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn sign_secret_one() {
+		let secret: &[u8] = &[
+			0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+			0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+			0x00,0x00,0x00,0x00,0x00,0x01,
+		];
+
+		// sha256("Satoshi Nakamoto")
+		let msg_hash = [
+			0xa0,0xdc,0x65,0xff,0xca,0x79,0x98,0x73,0xcb,0xea,0x0a,0xc2,0x74,
+			0x01,0x5b,0x95,0x26,0x50,0x5d,0xaa,0xae,0xd3,0x85,0x15,0x54,0x25,
+			0xf7,0x33,0x77,0x04,0x88,0x3e
+		];
+
+		let expected: &[u8] = &[
+			0x93,0x4b,0x1e,0xa1,0x0a,0x4b,0x3c,0x17,0x57,0xe2,0xb0,0xc0,0x17,
+			0xd0,0xb6,0x14,0x3c,0xe3,0xc9,0xa7,0xe6,0xa4,0xa4,0x98,0x60,0xd7,
+			0xa6,0xab,0x21,0x0e,0xe3,0xd8,0x24,0x42,0xce,0x9d,0x2b,0x91,0x60,
+			0x64,0x10,0x80,0x14,0x78,0x3e,0x92,0x3e,0xc3,0x6b,0x49,0x74,0x3e,
+			0x2f,0xfa,0x1c,0x44,0x96,0xf0,0x1a,0x51,0x2a,0xaf,0xd9,0xe5
+		];
+
+		let g = ECPointG::new();
+
+		let (sig, recid) = sign(&g, &msg_hash, secret).unwrap();
+
+		assert_eq!(&sig.to_bytes()[..], expected);
+		assert_eq!(recid, 1);
+	}
+
+	#[test]
+	fn sign_empty_phrase() {
+		let secret: &[u8] = &[
+			0x4d,0x5d,0xb4,0x10,0x7d,0x23,0x7d,0xf6,0xa3,0xd5,0x8e,0xe5,0xf7,
+			0x0a,0xe6,0x3d,0x73,0xd7,0x65,0x8d,0x40,0x26,0xf2,0xee,0xfd,0x2f,
+			0x20,0x4c,0x81,0x68,0x2c,0xb7
+		];
+
+		// sha256("")
+		let msg_hash = [
+			0xe3,0xb0,0xc4,0x42,0x98,0xfc,0x1c,0x14,0x9a,0xfb,0xf4,0xc8,0x99,
+			0x6f,0xb9,0x24,0x27,0xae,0x41,0xe4,0x64,0x9b,0x93,0x4c,0xa4,0x95,
+			0x99,0x1b,0x78,0x52,0xb8,0x55
+		];
+
+		let expected: &[u8] = &[
+			0x93,0xaf,0x06,0x63,0x5f,0x7e,0x9c,0xbc,0xf8,0xe3,0x47,0xff,0x11,
+			0xb7,0xfe,0xe4,0x34,0xc0,0x5f,0x65,0xf0,0x6a,0xe6,0x57,0x66,0x9e,
+			0xec,0xda,0xca,0x45,0xe0,0xd0,0x56,0xf4,0x6e,0xac,0x10,0x60,0x84,
+			0xbd,0xcc,0xcf,0x7e,0xda,0x50,0xc7,0xa0,0xd8,0xbd,0x51,0x4e,0xf1,
+			0x75,0xcc,0x25,0x7b,0x1a,0xa9,0x98,0xda,0x94,0x68,0xc3,0x22
+		];
+
+		let g = ECPointG::new();
+
+		let (sig, recid) = sign(&g, &msg_hash, secret).unwrap();
+
+		assert_eq!(&sig.to_bytes()[..], expected);
+		assert_eq!(recid, 0);
+	}
+
+	#[test]
+	fn sign_invalid_secret() {
+		let g = ECPointG::new();
+
+		assert_eq!(sign(&g, &[0x42; 32], &[0u8; 32]), Err(Error::InvalidSecretKey));
+		assert_eq!(sign(&g, &[0x42; 32], &[0xff; 32]), Err(Error::Overflow));
+		assert_eq!(sign(&g, &[0x42; 32], &[0x01; 31]), Err(Error::InvalidLength));
+	}
+
+	#[test]
+	fn verify_signature() {
+		// public key for secret `1`
+		let public_key: &[u8] = &[
+			0x04,0x79,0xbe,0x66,0x7e,0xf9,0xdc,0xbb,0xac,0x55,0xa0,0x62,0x95,
+			0xce,0x87,0x0b,0x07,0x02,0x9b,0xfc,0xdb,0x2d,0xce,0x28,0xd9,0x59,
+			0xf2,0x81,0x5b,0x16,0xf8,0x17,0x98,0x48,0x3a,0xda,0x77,0x26,0xa3,
+			0xc4,0x65,0x5d,0xa4,0xfb,0xfc,0x0e,0x11,0x08,0xa8,0xfd,0x17,0xb4,
+			0x48,0xa6,0x85,0x54,0x19,0x9c,0x47,0xd0,0x8f,0xfb,0x10,0xd4,0xb8
+		];
+
+		// sha256("Satoshi Nakamoto")
+		let msg_hash = [
+			0xa0,0xdc,0x65,0xff,0xca,0x79,0x98,0x73,0xcb,0xea,0x0a,0xc2,0x74,
+			0x01,0x5b,0x95,0x26,0x50,0x5d,0xaa,0xae,0xd3,0x85,0x15,0x54,0x25,
+			0xf7,0x33,0x77,0x04,0x88,0x3e
+		];
+
+		let signature = Signature::from_bytes(&[
+			0x93,0x4b,0x1e,0xa1,0x0a,0x4b,0x3c,0x17,0x57,0xe2,0xb0,0xc0,0x17,
+			0xd0,0xb6,0x14,0x3c,0xe3,0xc9,0xa7,0xe6,0xa4,0xa4,0x98,0x60,0xd7,
+			0xa6,0xab,0x21,0x0e,0xe3,0xd8,0x24,0x42,0xce,0x9d,0x2b,0x91,0x60,
+			0x64,0x10,0x80,0x14,0x78,0x3e,0x92,0x3e,0xc3,0x6b,0x49,0x74,0x3e,
+			0x2f,0xfa,0x1c,0x44,0x96,0xf0,0x1a,0x51,0x2a,0xaf,0xd9,0xe5
+		]).unwrap();
+
+		assert_eq!(verify(&msg_hash, &signature, public_key), Ok(()));
+
+		let mut other_hash = msg_hash;
+		other_hash[31] ^= 1;
+
+		assert_eq!(verify(&other_hash, &signature, public_key), Err(Error::InvalidSignature));
+
+		// same signature with high `s`
+		let high_s = Signature {
+			r: signature.r,
+			s: -signature.s
+		};
+
+		assert_eq!(verify(&msg_hash, &high_s, public_key), Err(Error::InvalidSignature));
+		assert_eq!(verify(&msg_hash, &signature, &public_key[..64]), Err(Error::InvalidLength));
+	}
+
+	#[test]
+	fn sign_and_verify() {
+		let secret: &[u8] = &[
+			0x4d,0x5d,0xb4,0x10,0x7d,0x23,0x7d,0xf6,0xa3,0xd5,0x8e,0xe5,0xf7,
+			0x0a,0xe6,0x3d,0x73,0xd7,0x65,0x8d,0x40,0x26,0xf2,0xee,0xfd,0x2f,
+			0x20,0x4c,0x81,0x68,0x2c,0xb7
+		];
+
+		let g = ECPointG::new();
+		let public_key = ::create_public_key(&g, secret).unwrap();
+		let compressed = ::create_public_key_compressed(&g, secret).unwrap();
+
+		for byte in 0..16 {
+			let msg_hash = [byte; 32];
+			let (signature, _) = sign(&g, &msg_hash, secret).unwrap();
+
+			assert!(verify(&msg_hash, &signature, &public_key).is_ok());
+			assert!(verify(&msg_hash, &signature, &compressed).is_ok());
+			assert!(verify(&[byte + 1; 32], &signature, &public_key).is_err());
+		}
+	}
+
+	#[test]
+	fn recover_public_key() {
+		let expected: &[u8] = &[
+			0x04,0x79,0xbe,0x66,0x7e,0xf9,0xdc,0xbb,0xac,0x55,0xa0,0x62,0x95,
+			0xce,0x87,0x0b,0x07,0x02,0x9b,0xfc,0xdb,0x2d,0xce,0x28,0xd9,0x59,
+			0xf2,0x81,0x5b,0x16,0xf8,0x17,0x98,0x48,0x3a,0xda,0x77,0x26,0xa3,
+			0xc4,0x65,0x5d,0xa4,0xfb,0xfc,0x0e,0x11,0x08,0xa8,0xfd,0x17,0xb4,
+			0x48,0xa6,0x85,0x54,0x19,0x9c,0x47,0xd0,0x8f,0xfb,0x10,0xd4,0xb8
+		];
+
+		// sha256("Satoshi Nakamoto")
+		let msg_hash = [
+			0xa0,0xdc,0x65,0xff,0xca,0x79,0x98,0x73,0xcb,0xea,0x0a,0xc2,0x74,
+			0x01,0x5b,0x95,0x26,0x50,0x5d,0xaa,0xae,0xd3,0x85,0x15,0x54,0x25,
+			0xf7,0x33,0x77,0x04,0x88,0x3e
+		];
+
+		let sig64 = [
+			0x93,0x4b,0x1e,0xa1,0x0a,0x4b,0x3c,0x17,0x57,0xe2,0xb0,0xc0,0x17,
+			0xd0,0xb6,0x14,0x3c,0xe3,0xc9,0xa7,0xe6,0xa4,0xa4,0x98,0x60,0xd7,
+			0xa6,0xab,0x21,0x0e,0xe3,0xd8,0x24,0x42,0xce,0x9d,0x2b,0x91,0x60,
+			0x64,0x10,0x80,0x14,0x78,0x3e,0x92,0x3e,0xc3,0x6b,0x49,0x74,0x3e,
+			0x2f,0xfa,0x1c,0x44,0x96,0xf0,0x1a,0x51,0x2a,0xaf,0xd9,0xe5
+		];
+
+		let key = recover(&msg_hash, &sig64, 1).unwrap();
+
+		assert_eq!(&key[..], expected);
+		assert!(recover(&msg_hash, &sig64, 0).unwrap()[..] != expected[..]);
+		assert_eq!(recover(&msg_hash, &sig64, 4), Err(Error::InvalidRecoveryId));
+	}
+
+	#[test]
+	fn sign_and_recover() {
+		let secret: &[u8] = &[
+			0x4d,0x5d,0xb4,0x10,0x7d,0x23,0x7d,0xf6,0xa3,0xd5,0x8e,0xe5,0xf7,
+			0x0a,0xe6,0x3d,0x73,0xd7,0x65,0x8d,0x40,0x26,0xf2,0xee,0xfd,0x2f,
+			0x20,0x4c,0x81,0x68,0x2c,0xb7
+		];
+
+		let g = ECPointG::new();
+		let public_key = ::create_public_key(&g, secret).unwrap();
+
+		for byte in 0..16 {
+			let msg_hash = [byte; 32];
+			let (signature, recid) = sign(&g, &msg_hash, secret).unwrap();
+			let key = recover(&msg_hash, &signature.to_bytes(), recid).unwrap();
+
+			assert_eq!(&key[..], &public_key[..]);
+		}
+	}
+
+	#[test]
+	fn recover_overflowing_r() {
+		// x coordinate of the nonce point is `N + 2`
+		let sig64 = [
+			0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+			0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+			0x00,0x00,0x00,0x00,0x00,0x02,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+			0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,
+			0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x01,0x23,0x45,0x67
+		];
+
+		let expected: &[u8] = &[
+			0x04,0xf1,0x89,0xb4,0x42,0x3b,0x7b,0x05,0x9f,0x95,0xcf,0x14,0xad,
+			0x84,0x73,0xb0,0x9f,0x75,0x41,0x6d,0x4c,0x4e,0x0a,0x44,0x93,0x49,
+			0x07,0x37,0xba,0x8f,0x57,0x5e,0xc1,0x68,0x01,0x42,0x04,0xf4,0x37,
+			0x76,0xf6,0x3b,0xe8,0x57,0xe4,0x0c,0x3f,0x95,0x58,0x27,0x8e,0x2b,
+			0x42,0xd8,0xc1,0x9c,0x99,0xfe,0x38,0x78,0x61,0xf9,0x0c,0x4f,0x8f
+		];
+
+		let msg_hash = [0x42; 32];
+		let key = recover(&msg_hash, &sig64, 3).unwrap();
+
+		assert_eq!(&key[..], expected);
+		assert!(verify(&msg_hash, &Signature::from_bytes(&sig64).unwrap(), &key).is_ok());
+
+		// r + N >= P, can't be lifted
+		let mut sig64 = sig64;
+		sig64[15] = 0x02;
+
+		assert_eq!(recover(&msg_hash, &sig64, 3), Err(Error::InvalidSignature));
+	}
+}
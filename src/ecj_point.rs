@@ -1,22 +1,61 @@
-use core::ops::{Add, AddAssign};
+// Style lints the original code predates
+#![allow(
+	clippy::needless_lifetimes,
+	clippy::redundant_field_names,
+	clippy::clone_on_copy,
+	clippy::bool_assert_comparison
+)]
+
+use core::ops::{Add, AddAssign, Sub, SubAssign, Mul, Neg};
 use big_num::{self, BigNum};
+use field::{self, FieldElement};
+use scalar::Scalar;
 use ec_point::{self, ECPoint};
 
-#[derive(Copy, Clone, Debug, PartialEq)]
+/// A point in Jacobian coordinates, representing the affine point
+/// `(x / z^2, y / z^3)`, or infinity when `z` is zero.
+#[derive(Copy, Clone, Debug)]
 pub struct ECJPoint {
-	pub x: BigNum,
-	pub y: BigNum,
-	pub z: BigNum
+	pub x: FieldElement,
+	pub y: FieldElement,
+	pub z: FieldElement
 }
 
+/// The point at infinity, identity of the group
+pub const INF: ECJPoint = ECJPoint {
+	x: field::ONE,
+	y: field::ONE,
+	z: field::ZERO
+};
+
 impl Default for ECJPoint {
 	#[inline]
 	fn default() -> Self {
-		ECJPoint {
-			x: big_num::ONE,
-			y: big_num::ONE,
-			z: big_num::ZERO
+		INF
+	}
+}
+
+/// Compares the represented points, without converting to affine
+/// coordinates: `x1 * z2^2 == x2 * z1^2` and `y1 * z2^3 == y2 * z1^3`.
+impl PartialEq for ECJPoint {
+	fn eq(&self, other: &ECJPoint) -> bool {
+		if self.inf() || other.inf() {
+			return self.inf() == other.inf();
 		}
+
+		let z1z1 = self.z.sqr();
+		let z2z2 = other.z.sqr();
+
+		self.x * &z2z2 == other.x * &z1z1 &&
+		self.y * &z2z2 * &other.z == other.y * &z1z1 * &self.z
+	}
+}
+
+impl Eq for ECJPoint {}
+
+impl PartialEq<ECPoint> for ECJPoint {
+	fn eq(&self, other: &ECPoint) -> bool {
+		*self == ECJPoint::from(*other)
 	}
 }
 
@@ -27,7 +66,7 @@ impl From<ECPoint> for ECJPoint {
 			return ECJPoint::default();
 		}
 
-		ECJPoint::new(val.x, val.y, big_num::ONE)
+		ECJPoint::new(val.x, val.y, field::ONE)
 	}
 }
 
@@ -37,13 +76,7 @@ impl From<ECJPoint> for ECPoint {
 			return ec_point::INF;
 		}
 
-		let zinv = val.z.red_invm();
-		let zinv2 = zinv.red_sqr();
-		let ax = val.x.red_mul(&zinv2);
-		let mut ay = val.y.red_mul(&zinv2);
-		ay.red_mul_mut(&zinv);
-
-		ECPoint::new(ax, ay)
+		val.to_affine(&val.z.inv())
 	}
 }
 
@@ -72,18 +105,18 @@ impl<'a> AddAssign<&'a ECJPoint> for ECJPoint {
 
 		// http://hyperelliptic.org/EFD/g1p/auto-shortw-jacobian-0.html#addition-add-1998-cmo-2
 		// 12M + 4S + 7A
-		let pz2 = p.z.red_sqr();
-		let z2 = self.z.red_sqr();
-		let u1 = self.x.red_mul(&pz2);
-		let u2 = p.x.red_mul(&z2);
-		let s1 = self.y.red_mul(&pz2).red_mul(&p.z);
-		let s2 = p.y.red_mul(&z2).red_mul(&self.z);
-
-		let h = u1.red_sub(&u2);
-		let r = s1.red_sub(&s2);
-
-		if h == 0 {
-			if r == 0 {
+		let pz2 = p.z.sqr();
+		let z2 = self.z.sqr();
+		let u1 = self.x * &pz2;
+		let u2 = p.x * &z2;
+		let s1 = self.y * &pz2 * &p.z;
+		let s2 = p.y * &z2 * &self.z;
+
+		let h = u1 - &u2;
+		let r = s1 - &s2;
+
+		if h.is_zero() {
+			if r.is_zero() {
 				self.double();
 				return;
 			}
@@ -92,19 +125,87 @@ impl<'a> AddAssign<&'a ECJPoint> for ECJPoint {
 			return;
 		}
 
-		let h2 = h.red_sqr();
-		let v = u1.red_mul(&h2);
-		let h3 = h2.red_mul(&h);
+		let h2 = h.sqr();
+		let v = u1 * &h2;
+		let h3 = h2 * &h;
+
+		self.x = r.sqr() + &h3 - &v - &v;
+		self.y = r * &(v - &self.x) - &(s1 * &h3);
+		self.z = self.z * &p.z * &h;
+	}
+}
+
+impl Add<&ECPoint> for ECJPoint {
+	type Output = ECJPoint;
+
+	#[inline]
+	fn add(mut self, p: &ECPoint) -> ECJPoint {
+		self.mixed_add(p);
+		self
+	}
+}
+
+impl AddAssign<&ECPoint> for ECJPoint {
+	#[inline]
+	fn add_assign(&mut self, p: &ECPoint) {
+		self.mixed_add(p);
+	}
+}
+
+impl Sub<&ECJPoint> for ECJPoint {
+	type Output = ECJPoint;
+
+	#[inline]
+	fn sub(mut self, p: &ECJPoint) -> ECJPoint {
+		self.sub_assign(p);
+		self
+	}
+}
+
+impl SubAssign<&ECJPoint> for ECJPoint {
+	#[inline]
+	fn sub_assign(&mut self, p: &ECJPoint) {
+		self.add_assign(&-*p);
+	}
+}
+
+impl Sub<&ECPoint> for ECJPoint {
+	type Output = ECJPoint;
+
+	#[inline]
+	fn sub(mut self, p: &ECPoint) -> ECJPoint {
+		self.sub_assign(p);
+		self
+	}
+}
 
-		self.x = r.red_sqr().red_add(&h3).red_sub(&v).red_sub(&v);
-		self.y = r.red_mul(&v.red_sub(&self.x)).red_sub(&s1.red_mul(&h3));
-		self.z = self.z.red_mul(&p.z).red_mul(&h);
+impl SubAssign<&ECPoint> for ECJPoint {
+	#[inline]
+	fn sub_assign(&mut self, p: &ECPoint) {
+		self.mixed_add(&-*p);
+	}
+}
+
+impl Neg for ECJPoint {
+	type Output = ECJPoint;
+
+	#[inline]
+	fn neg(self) -> ECJPoint {
+		ECJPoint::new(self.x, -self.y, self.z)
+	}
+}
+
+impl Mul<&Scalar> for ECJPoint {
+	type Output = ECJPoint;
+
+	fn mul(self, k: &Scalar) -> ECJPoint {
+		ECPoint::from(self) * k
 	}
 }
 
 impl ECJPoint {
 	#[inline]
-	pub fn new(x: BigNum, y: BigNum, z: BigNum) -> Self {
+	pub fn new(x: FieldElement, y: FieldElement, z: FieldElement) -> Self {
 		ECJPoint {
 			x: x,
 			y: y,
@@ -127,32 +228,94 @@ impl ECJPoint {
 		// http://hyperelliptic.org/EFD/g1p/auto-shortw-jacobian-0.html#addition-add-1998-cmo-2
 		//   with p.z = 1
 		// 8M + 3S + 7A
-		let z2 = self.z.red_sqr();
-		let u2 = p.x.red_mul(&z2);
-		let mut s2 = p.y.red_mul(&z2);
-		s2.red_mul_mut(&self.z);
+		let z2 = self.z.sqr();
+		let u2 = p.x * &z2;
+		let s2 = p.y * &z2 * &self.z;
 
-		let h = self.x.red_sub(&u2);
-		let r = self.y.red_sub(&s2);
+		let h = self.x - &u2;
+		let r = self.y - &s2;
 
-		if h == 0 {
-			if r == 0 {
+		if h.is_zero() {
+			if r.is_zero() {
 				self.double();
 				return;
 			}
-			self.x = big_num::ONE;
-			self.y = big_num::ONE;
-			self.z = big_num::ZERO;
+			*self = ECJPoint::default();
 			return;
 		}
 
-		let h2 = h.red_sqr();
-		let v = self.x.red_mul(&h2);
-		let h3 = h2.red_mul(&h);
+		let h2 = h.sqr();
+		let v = self.x * &h2;
+		let h3 = h2 * &h;
+
+		self.x = r.sqr() + &h3 - &v - &v;
+		self.y = r * &(v - &self.x) - &(self.y * &h3);
+		self.z *= &h;
+	}
+
+	/// Same as `mixed_add`, but without branching on the values. `p` must
+	/// not be infinity, and `self` must not be equal to `p` or `-p`, or
+	/// the result is wrong. `ECPointG::mul` guarantees this by construction
+	/// without blinding, while with `ECPointG::randomize` a collision is
+	/// not impossible, only negligibly likely.
+	pub fn mixed_add_ct(&mut self, p: &ECPoint) {
+		let inf = self.inf();
+
+		let z2 = self.z.sqr();
+		let u2 = p.x * &z2;
+		let s2 = p.y * &z2 * &self.z;
+
+		let h = self.x - &u2;
+		let r = self.y - &s2;
+
+		let h2 = h.sqr();
+		let v = self.x * &h2;
+		let h3 = h2 * &h;
+
+		let nx = r.sqr() + &h3 - &v - &v;
+		let ny = r * &(v - &nx) - &(self.y * &h3);
+
+		let mut sum = ECJPoint::new(nx, ny, self.z * &h);
+
+		// O + P = P
+		sum.cmov(&ECJPoint::new(p.x, p.y, field::ONE), inf);
 
-		self.x = r.red_sqr().red_add(&h3).red_sub_twice(&v);
-		self.y = r.red_mul(&v.red_sub(&self.x)).red_sub(&self.y.red_mul(&h3));
-		self.z.red_mul_mut(&h);
+		*self = sum;
+	}
+
+	/// Copy `src` into `self` if `flag` is set, without branching on it.
+	#[inline]
+	pub fn cmov(&mut self, src: &ECJPoint, flag: bool) {
+		self.x.cmov(&src.x, flag);
+		self.y.cmov(&src.y, flag);
+		self.z.cmov(&src.z, flag);
+	}
+
+	/// Same as `double`, but without branching on the values. Infinity
+	/// needs no special case, as its doubled `z` stays zero.
+	pub fn double_ct(&mut self) {
+		// http://hyperelliptic.org/EFD/g1p/auto-shortw-jacobian-0.html#doubling-dbl-2009-l
+		// 2M + 5S + 6A + 3*2 + 1*3 + 1*8
+		let a = self.x.sqr();
+		let b = self.y.sqr();
+		let c = b.sqr();
+		let mut d = (self.x + &b).sqr() - &a - &c;
+		d = d + &d;
+		let e = a + &a + &a;
+		let f = e.sqr();
+
+		let mut c8 = c;
+		c8 = c8 + &c8; // x2
+		c8 = c8 + &c8; // x4
+		c8 = c8 + &c8; // x8
+
+		let nx = f - &d - &d;
+		let ny = e * &(d - &nx) - &c8;
+		let yz = self.y * &self.z;
+
+		self.x = nx;
+		self.y = ny;
+		self.z = yz + &yz;
 	}
 
 	pub fn double(&mut self) {
@@ -162,47 +325,47 @@ impl ECJPoint {
 
 		let nx;
 		let ny;
-		let mut nz;
+		let nz;
 
-		if self.z == 1 {
+		if self.z == field::ONE {
 			// http://hyperelliptic.org/EFD/g1p/auto-shortw-jacobian-0.html#doubling-mdbl-2007-bl
 			// 1M + 5S + 6A + 3*2 + 1*3 + 1*8
-			let xx = self.x.red_sqr();
-			let yy = self.y.red_sqr();
-			let yyyy = yy.red_sqr();
-			let mut s = self.x.red_add(&yy).red_sqr().red_sub(&xx).red_sub(&yyyy);
-			s.red_double();
-			let m = xx.red_add(&xx).red_add(&xx);
-			let t = m.red_sqr().red_sub(&s).red_sub(&s);
+			let xx = self.x.sqr();
+			let yy = self.y.sqr();
+			let yyyy = yy.sqr();
+			let mut s = (self.x + &yy).sqr() - &xx - &yyyy;
+			s = s + &s;
+			let m = xx + &xx + &xx;
+			let t = m.sqr() - &s - &s;
 
 			let mut yyyy8 = yyyy;
-			yyyy8.red_double(); // x2
-			yyyy8.red_double(); // x4
-			yyyy8.red_double(); // x8
+			yyyy8 = yyyy8 + &yyyy8; // x2
+			yyyy8 = yyyy8 + &yyyy8; // x4
+			yyyy8 = yyyy8 + &yyyy8; // x8
 
 			nx = t;
-			ny = m.red_mul(&s.red_sub(&t)).red_sub(&yyyy8);
-			nz = self.y.red_add(&self.y);
+			ny = m * &(s - &t) - &yyyy8;
+			nz = self.y + &self.y;
 		} else {
 			// http://hyperelliptic.org/EFD/g1p/auto-shortw-jacobian-0.html#doubling-dbl-2009-l
 			// 2M + 5S + 6A + 3*2 + 1*3 + 1*8
-			let a = self.x.red_sqr();
-			let b = self.y.red_sqr();
-			let c = b.red_sqr();
-			let mut d = self.x.red_add(&b).red_sqr().red_sub(&a).red_sub(&c);
-			d.red_double();
-			let e = a.red_add(&a).red_add(&a);
-			let f = e.red_sqr();
+			let a = self.x.sqr();
+			let b = self.y.sqr();
+			let c = b.sqr();
+			let mut d = (self.x + &b).sqr() - &a - &c;
+			d = d + &d;
+			let e = a + &a + &a;
+			let f = e.sqr();
 
 			let mut c8 = c;
-			c8.red_double(); // x2
-			c8.red_double(); // x4
-			c8.red_double(); // x8
-
-			nx = f.red_sub(&d).red_sub(&d);
-			ny = e.red_mul(&d.red_sub(&nx)).red_sub(&c8);
-			nz = self.y.red_mul(&self.z);
-			nz = nz.red_add(&nz);
+			c8 = c8 + &c8; // x2
+			c8 = c8 + &c8; // x4
+			c8 = c8 + &c8; // x8
+
+			nx = f - &d - &d;
+			ny = e * &(d - &nx) - &c8;
+			let yz = self.y * &self.z;
+			nz = yz + &yz;
 		}
 
 		self.x = nx;
@@ -210,15 +373,94 @@ impl ECJPoint {
 		self.z = nz;
 	}
 
+	/// Check whether the affine x coordinate of this point, reduced
+	/// modulo `N`, equals `x`, without converting out of Jacobian
+	/// coordinates: compares `x * z^2` and `(x + N) * z^2` to `self.x`.
+	pub fn eq_x_to_p(&self, x: &Scalar) -> bool {
+		let zs = self.z.sqr();
+		let rx = FieldElement::from_bytes_reduced(&x.to_bytes()) * &zs;
+
+		if self.x == rx {
+			return true;
+		}
+
+		// x + N would be no longer a valid field element
+		if &BigNum::from(x) >= big_num::PSN {
+			return false;
+		}
+
+		let t = field::ORDER * &zs;
+
+		self.x == rx + &t
+	}
+
+	/// Same as `ECPoint::from`, but the inversion is not constant time,
+	/// so this must only be used on public points.
+	pub fn to_affine_vartime(self) -> ECPoint {
+		if self.inf() {
+			return ec_point::INF;
+		}
+
+		self.to_affine(&self.z.inv_vartime())
+	}
+
+	/// Convert all `points` to affine coordinates into `out`, which must
+	/// be of the same length, with a single inversion (Montgomery's trick).
+	pub fn batch_normalize(points: &[ECJPoint], out: &mut [ECPoint]) {
+		assert_eq!(points.len(), out.len());
+
+		// out[i].x holds the product of all non-infinity z up to i
+		let mut acc = field::ONE;
+
+		for (point, o) in points.iter().zip(out.iter_mut()) {
+			if !point.inf() {
+				acc *= &point.z;
+			}
+
+			o.x = acc;
+		}
+
+		// inverse of the product of all z up to i
+		let mut inv = acc.inv();
+
+		for i in (0..points.len()).rev() {
+			let point = &points[i];
+
+			if point.inf() {
+				out[i] = ec_point::INF;
+				continue;
+			}
+
+			let prev = if i > 0 { out[i - 1].x } else { field::ONE };
+			let zinv = inv * &prev;
+
+			inv *= &point.z;
+			out[i] = point.to_affine(&zinv);
+		}
+	}
+
+	fn to_affine(self, zinv: &FieldElement) -> ECPoint {
+		let zinv2 = zinv.sqr();
+		let ax = self.x * &zinv2;
+		let ay = self.y * &zinv2 * zinv;
+
+		ECPoint::new(ax, ay)
+	}
+
 	#[inline]
 	pub fn inf(&self) -> bool {
-		self.z == 0
+		self.z.is_zero()
 	}
 }
 
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use core::convert::TryFrom;
+
+	fn fe(bytes: &[u8]) -> FieldElement {
+		FieldElement::try_from(bytes).unwrap()
+	}
 
 	#[test]
 	fn ecj_point_mixed_add() {
@@ -234,9 +476,9 @@ mod tests {
 			0x11,0x08,0xa8,0xfd,0x17,0xb4,0x48,0xa6,0x85,0x54,0x19,0x9c,0x47,
 			0xd0,0x8f,0xfb,0x10,0xd4,0xb8
 		];
-		let ecpoint = ECPoint::new(x.into(), y.into());
+		let ecpoint = ECPoint::new(fe(x), fe(y));
 
-		let expected = ECJPoint::new(x.into(), y.into(), 1u32.into());
+		let expected = ECJPoint::new(fe(x), fe(y), 1u32.into());
 
 		assert_eq!(ecj.inf(), true);
 		ecj.mixed_add(&ecpoint);
@@ -293,10 +535,47 @@ mod tests {
 			0xb8,0x98,0xe4,0x85,0x9c,0x5b
 		];
 
-		let a = ECJPoint::new(xa.into(), ya.into(), za.into());
-		let b = ECJPoint::new(xb.into(), yb.into(), zb.into());
-		let r = ECJPoint::new(xr.into(), yr.into(), zr.into());
+		let a = ECJPoint::new(fe(xa), fe(ya), fe(za));
+		let b = ECJPoint::new(fe(xb), fe(yb), fe(zb));
+		let r = ECJPoint::new(fe(xr), fe(yr), fe(zr));
 
 		assert_eq!(a + &b, r);
 	}
+
+	#[test]
+	fn ecj_point_batch_normalize() {
+		let g = ECJPoint::from(ec_point::G);
+		let points = [g, g + &g, INF, -g, g + &g + &g, INF];
+		let mut out = [ec_point::G; 6];
+
+		ECJPoint::batch_normalize(&points, &mut out);
+
+		for (point, affine) in points.iter().zip(out.iter()) {
+			assert_eq!(ECPoint::from(*point), *affine);
+			assert_eq!(affine.inf, point.inf());
+		}
+
+		ECJPoint::batch_normalize(&[], &mut []);
+	}
+
+	#[test]
+	fn ecj_point_eq() {
+		let g = ECJPoint::from(ec_point::G);
+		let l = FieldElement::from(0x1337);
+		let l2 = l.sqr();
+
+		// the same point with z = l
+		let scaled = ECJPoint::new(g.x * &l2, g.y * &l2 * &l, l);
+
+		assert_eq!(scaled, g);
+		assert_eq!(scaled, ec_point::G);
+		assert!(scaled != -g);
+		assert!(scaled != INF);
+		assert_eq!(ECPoint::from(scaled), ec_point::G);
+
+		assert_eq!(INF, ECJPoint::new(l, l, field::ZERO));
+		assert_eq!(scaled - &g, INF);
+		assert_eq!(scaled + &-scaled, INF);
+		assert_eq!(scaled * &Scalar::from(2), g + &g);
+	}
 }
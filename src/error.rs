@@ -0,0 +1,64 @@
+use core::fmt;
+
+/// Errors returned throughout the crate, more variants may be added as
+/// the crate grows
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+	/// The input doesn't have the expected length
+	InvalidLength,
+	/// The number is not below the modulus
+	Overflow,
+	/// The secret key is zero
+	InvalidSecretKey,
+	/// The public key is malformed, infinity or not on the curve
+	InvalidPublicKey,
+	/// The signature is malformed or doesn't match the message and key
+	InvalidSignature,
+	/// The recovery id is not in `0..=3`
+	InvalidRecoveryId,
+	/// The tweak overflows the group order or results in an invalid key
+	InvalidTweak,
+	/// A character outside of the Base58 alphabet, or a wrong checksum
+	InvalidBase58,
+	/// The extended key has the wrong version, or a depth inconsistent
+	/// with its parent fingerprint and child number
+	InvalidExtendedKey,
+	/// Hardened children can't be derived from an extended public key
+	HardenedDerivation
+}
+
+impl fmt::Display for Error {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str(match *self {
+			Error::InvalidLength => "invalid input length",
+			Error::Overflow => "number is not below the modulus",
+			Error::InvalidSecretKey => "invalid secret key",
+			Error::InvalidPublicKey => "invalid public key",
+			Error::InvalidSignature => "invalid signature",
+			Error::InvalidRecoveryId => "invalid recovery id",
+			Error::InvalidTweak => "invalid tweak",
+			Error::InvalidBase58 => "invalid Base58 encoding or checksum",
+			Error::InvalidExtendedKey => "invalid extended key",
+			Error::HardenedDerivation => "hardened derivation from a public key"
+		})
+	}
+}
+
+#[cfg(feature = "std")]
+impl ::std::error::Error for Error {}
+
+#[cfg(test)]
+mod tests {
+	extern crate std;
+
+	use super::*;
+	use self::std::string::ToString;
+
+	#[test]
+	fn error_display() {
+		assert_eq!(Error::InvalidLength.to_string(), "invalid input length");
+		assert_eq!(Error::Overflow.to_string(), "number is not below the modulus");
+		assert_eq!(Error::InvalidRecoveryId.to_string(), "invalid recovery id");
+	}
+}
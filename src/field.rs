@@ -0,0 +1,361 @@
+use core::ops::{Add, AddAssign, Sub, SubAssign, Mul, MulAssign, Neg};
+use core::convert::TryFrom;
+use error::Error;
+use words;
+use modinv;
+
+/// An element of the field of integers modulo `P`. Unlike `BigNum`,
+/// the value is always kept fully reduced in fixed 8 words, so there
+/// is no sign or length to keep track of and no branching on the value.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct FieldElement {
+	words: [u32; 8]
+}
+
+// FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEFFFFFC2F
+const P: [u32; 8] = [
+	0xfffffc2f, 0xfffffffe, 0xffffffff, 0xffffffff,
+	0xffffffff, 0xffffffff, 0xffffffff, 0xffffffff
+];
+
+// 2^256 - P = 1000003D1
+const PC: [u32; 2] = [0x000003d1, 0x00000001];
+
+// (P + 1) / 4, exponent for the square root
+const P_ADD_1_DIV_4: [u32; 8] = [
+	0xbfffff0c, 0xffffffff, 0xffffffff, 0xffffffff,
+	0xffffffff, 0xffffffff, 0xffffffff, 0x3fffffff
+];
+
+pub const ZERO: FieldElement = FieldElement { words: [0; 8] };
+
+pub const ONE: FieldElement = FieldElement { words: [1, 0, 0, 0, 0, 0, 0, 0] };
+
+/// Curve constant `b` in `y^2 = x^3 + b`
+pub const B: FieldElement = FieldElement { words: [7, 0, 0, 0, 0, 0, 0, 0] };
+
+// 79BE667EF9DCBBAC55A06295CE870B07029BFCDB2DCE28D959F2815B16F81798
+pub const GX: FieldElement = FieldElement {
+	words: [
+		0x16f81798, 0x59f2815b, 0x2dce28d9, 0x029bfcdb,
+		0xce870b07, 0x55a06295, 0xf9dcbbac, 0x79be667e
+	]
+};
+
+// 483ADA7726A3C4655DA4FBFC0E1108A8FD17B448A68554199C47D08FFB10D4B8
+pub const GY: FieldElement = FieldElement {
+	words: [
+		0xfb10d4b8, 0x9c47d08f, 0xa6855419, 0xfd17b448,
+		0x0e1108a8, 0x5da4fbfc, 0x26a3c465, 0x483ada77
+	]
+};
+
+/// A cube root of unity modulo `P`, see `scalar::LAMBDA`
+pub const BETA: FieldElement = FieldElement {
+	words: [
+		0x719501ee, 0xc1396c28, 0x12f58995, 0x9cf04975,
+		0xac3434e9, 0x6e64479e, 0x657c0710, 0x7ae96a2b
+	]
+};
+
+/// The group order `N` as a field element
+pub const ORDER: FieldElement = FieldElement {
+	words: [
+		0xd0364141, 0xbfd25e8c, 0xaf48a03b, 0xbaaedce6,
+		0xfffffffe, 0xffffffff, 0xffffffff, 0xffffffff
+	]
+};
+
+impl FieldElement {
+	/// Build from little-endian words, which must already be below `P`.
+	pub(crate) const fn from_words(words: [u32; 8]) -> FieldElement {
+		FieldElement { words }
+	}
+
+	/// Little-endian words
+	#[inline]
+	pub(crate) fn to_words(self) -> [u32; 8] {
+		self.words
+	}
+
+	/// Reads a big-endian 32 bytes integer, fails with `Overflow` if
+	/// it's not below `P`.
+	pub fn from_bytes(bytes: &[u8; 32]) -> Result<FieldElement, Error> {
+		let mut words = words::from_be_bytes(bytes);
+
+		if words::reduce(&mut words, 0, &PC) == 1 {
+			Err(Error::Overflow)
+		} else {
+			Ok(FieldElement { words })
+		}
+	}
+
+	/// Reads a big-endian 32 bytes integer, reducing it modulo `P`.
+	pub fn from_bytes_reduced(bytes: &[u8; 32]) -> FieldElement {
+		let mut words = words::from_be_bytes(bytes);
+
+		words::reduce(&mut words, 0, &PC);
+
+		FieldElement { words }
+	}
+
+	/// Reads a little-endian 32 bytes integer, fails with `Overflow` if
+	/// it's not below `P`.
+	pub fn from_le_bytes(bytes: &[u8; 32]) -> Result<FieldElement, Error> {
+		let mut be = *bytes;
+		be.reverse();
+
+		FieldElement::from_bytes(&be)
+	}
+
+	/// Big-endian bytes
+	#[inline]
+	pub fn to_bytes(self) -> [u8; 32] {
+		words::to_be_bytes(&self.words)
+	}
+
+	#[inline]
+	pub fn to_le_bytes(self) -> [u8; 32] {
+		words::to_le_bytes(&self.words)
+	}
+
+	/// Copy `src` into `self` if `flag` is set, without branching on it.
+	#[inline]
+	pub fn cmov(&mut self, src: &FieldElement, flag: bool) {
+		words::select(&mut self.words, &src.words, flag as u32);
+	}
+
+	#[inline]
+	pub fn is_zero(&self) -> bool {
+		words::is_zero(&self.words)
+	}
+
+	#[inline]
+	pub fn is_odd(&self) -> bool {
+		self.words[0] & 1 == 1
+	}
+
+	#[inline]
+	pub fn sqr(&self) -> FieldElement {
+		*self * self
+	}
+
+	fn pow(&self, exp: &[u32; 8]) -> FieldElement {
+		let mut res = ONE;
+
+		for word in exp.iter().rev() {
+			for bit in (0..32).rev() {
+				res = res.sqr();
+
+				if (word >> bit) & 1 == 1 {
+					res *= self;
+				}
+			}
+		}
+
+		res
+	}
+
+	/// Multiplicative inverse in constant time, zero maps to zero.
+	pub fn inv(&self) -> FieldElement {
+		FieldElement {
+			words: modinv::inv(&self.words, &modinv::P)
+		}
+	}
+
+	/// Multiplicative inverse, only to be used with public values.
+	pub fn inv_vartime(&self) -> FieldElement {
+		FieldElement {
+			words: modinv::inv_vartime(&self.words, &modinv::P)
+		}
+	}
+
+	/// Square root, if there is one. Since `P = 3 (mod 4)` the root
+	/// candidate is simply `self ^ ((P + 1) / 4)`.
+	pub fn sqrt(&self) -> Option<FieldElement> {
+		let root = self.pow(&P_ADD_1_DIV_4);
+
+		if root.sqr() == *self {
+			Some(root)
+		} else {
+			None
+		}
+	}
+}
+
+impl From<u32> for FieldElement {
+	fn from(n: u32) -> Self {
+		FieldElement {
+			words: [n, 0, 0, 0, 0, 0, 0, 0]
+		}
+	}
+}
+
+/// Reads a big-endian 32 bytes integer, failing if the slice is not
+/// exactly 32 bytes long or the number is not below `P`.
+impl<'a> TryFrom<&'a [u8]> for FieldElement {
+	type Error = Error;
+
+	fn try_from(buf: &'a [u8]) -> Result<Self, Error> {
+		if buf.len() != 32 {
+			return Err(Error::InvalidLength);
+		}
+
+		let mut bytes = [0u8; 32];
+		bytes.copy_from_slice(buf);
+
+		FieldElement::from_bytes(&bytes)
+	}
+}
+
+impl Add<&FieldElement> for FieldElement {
+	type Output = FieldElement;
+
+	#[inline]
+	fn add(mut self, rhs: &FieldElement) -> FieldElement {
+		self.add_assign(rhs);
+		self
+	}
+}
+
+impl AddAssign<&FieldElement> for FieldElement {
+	#[inline]
+	fn add_assign(&mut self, rhs: &FieldElement) {
+		words::add(&mut self.words, &rhs.words, &PC);
+	}
+}
+
+impl Sub<&FieldElement> for FieldElement {
+	type Output = FieldElement;
+
+	#[inline]
+	fn sub(mut self, rhs: &FieldElement) -> FieldElement {
+		self.sub_assign(rhs);
+		self
+	}
+}
+
+impl SubAssign<&FieldElement> for FieldElement {
+	#[inline]
+	fn sub_assign(&mut self, rhs: &FieldElement) {
+		self.add_assign(&-*rhs);
+	}
+}
+
+impl Mul<&FieldElement> for FieldElement {
+	type Output = FieldElement;
+
+	#[inline]
+	fn mul(mut self, rhs: &FieldElement) -> FieldElement {
+		self.mul_assign(rhs);
+		self
+	}
+}
+
+impl MulAssign<&FieldElement> for FieldElement {
+	#[inline]
+	fn mul_assign(&mut self, rhs: &FieldElement) {
+		let wide = words::mul_wide(&self.words, &rhs.words);
+
+		self.words = words::reduce_wide(&wide, &PC);
+	}
+}
+
+impl Neg for FieldElement {
+	type Output = FieldElement;
+
+	#[inline]
+	fn neg(self) -> FieldElement {
+		FieldElement {
+			words: words::neg(&self.words, &P)
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use big_num::{self, BigNum};
+
+	#[test]
+	fn field_constants() {
+		let g_x: &[u8] = &[
+			0x79,0xBE,0x66,0x7E,0xF9,0xDC,0xBB,0xAC,0x55,0xA0,0x62,0x95,0xCE,
+			0x87,0x0B,0x07,0x02,0x9B,0xFC,0xDB,0x2D,0xCE,0x28,0xD9,0x59,0xF2,
+			0x81,0x5B,0x16,0xF8,0x17,0x98
+		];
+		let g_y: &[u8] = &[
+			0x48,0x3A,0xDA,0x77,0x26,0xA3,0xC4,0x65,0x5D,0xA4,0xFB,0xFC,0x0E,
+			0x11,0x08,0xA8,0xFD,0x17,0xB4,0x48,0xA6,0x85,0x54,0x19,0x9C,0x47,
+			0xD0,0x8F,0xFB,0x10,0xD4,0xB8
+		];
+
+		assert_eq!(FieldElement::try_from(g_x), Ok(GX));
+		assert_eq!(FieldElement::try_from(g_y), Ok(GY));
+		assert_eq!(BigNum::from_be_bytes(&ORDER.to_bytes()), *big_num::N);
+		assert_eq!(-ONE + &ONE, ZERO);
+	}
+
+	#[test]
+	fn field_from_bytes() {
+		let p: [u8; 32] = [
+			0xFF,0xFF,0xFF,0xFF,0xFF,0xFF,0xFF,0xFF,0xFF,0xFF,0xFF,0xFF,0xFF,
+			0xFF,0xFF,0xFF,0xFF,0xFF,0xFF,0xFF,0xFF,0xFF,0xFF,0xFF,0xFF,0xFF,
+			0xFF,0xFE,0xFF,0xFF,0xFC,0x2F
+		];
+
+		assert_eq!(FieldElement::from_bytes(&p), Err(Error::Overflow));
+		assert_eq!(FieldElement::from_bytes_reduced(&p), ZERO);
+		assert_eq!(FieldElement::try_from(&p[..]), Err(Error::Overflow));
+		assert_eq!(FieldElement::try_from(&p[1..]), Err(Error::InvalidLength));
+
+		let mut p_sub_1 = p;
+		p_sub_1[31] -= 1;
+
+		assert_eq!(FieldElement::from_bytes(&p_sub_1), Ok(-ONE));
+
+		p_sub_1.reverse();
+
+		assert_eq!(FieldElement::from_le_bytes(&p_sub_1), Ok(-ONE));
+		assert_eq!((-ONE).to_le_bytes(), p_sub_1);
+	}
+
+	#[test]
+	fn field_mul() {
+		// (P - 1)^2 = 1
+		assert_eq!(-ONE * &-ONE, ONE);
+
+		// matches the `red_reduce` test for N * NC
+		let expected: &[u8] = &[
+			0x62,0x98,0xe3,0x2a,0x7e,0x39,0x64,0x3a,0x19,0x68,0x0a,0x1c,0xe9,
+			0x84,0x20,0x2d,0xac,0x9a,0xdf,0xb8,0x8e,0x3c,0x84,0xb7,0xd4,0xaf,
+			0x96,0xb5,0x28,0xe2,0xdc,0xcc
+		];
+
+		let nc = FieldElement::from_bytes(&big_num::NC.to_be_bytes()).unwrap();
+
+		assert_eq!(&(ORDER * &nc).to_bytes()[..], expected);
+	}
+
+	#[test]
+	fn field_inv() {
+		let x = FieldElement::from(0x1337);
+
+		assert_eq!(x.inv() * &x, ONE);
+		assert_eq!(x.inv_vartime(), x.inv());
+		assert_eq!((-ONE).inv(), -ONE);
+		assert_eq!((-ONE).inv_vartime(), -ONE);
+		assert_eq!(ZERO.inv(), ZERO);
+		assert_eq!(ZERO.inv_vartime(), ZERO);
+	}
+
+	#[test]
+	fn field_sqrt() {
+		let root = GY.sqr().sqrt().unwrap();
+
+		assert!(root == GY || root == -GY);
+
+		// 7 is not a quadratic residue modulo P
+		assert_eq!(B.sqrt(), None);
+	}
+}
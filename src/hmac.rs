@@ -0,0 +1,191 @@
+use sha256::{self, Sha256};
+use sha512::{self, Sha512};
+use words;
+
+const IPAD: u8 = 0x36;
+const OPAD: u8 = 0x5c;
+
+// the largest `Hash::BLOCK_SIZE`, for the key block
+const MAX_BLOCK_SIZE: usize = sha512::BLOCK_SIZE;
+
+/// A hash function HMAC can be built on. The key goes through its state,
+/// so implementations are expected to wipe it on drop.
+pub trait Hash: Clone {
+	const BLOCK_SIZE: usize;
+
+	type Output: AsRef<[u8]> + AsMut<[u8]>;
+
+	fn new() -> Self;
+	fn digest(data: &[u8]) -> Self::Output;
+	fn update(&mut self, data: &[u8]);
+	fn finalize(self) -> Self::Output;
+}
+
+impl Hash for Sha256 {
+	const BLOCK_SIZE: usize = sha256::BLOCK_SIZE;
+
+	type Output = [u8; 32];
+
+	#[inline]
+	fn new() -> Self {
+		Sha256::new()
+	}
+
+	#[inline]
+	fn digest(data: &[u8]) -> [u8; 32] {
+		Sha256::digest(data)
+	}
+
+	#[inline]
+	fn update(&mut self, data: &[u8]) {
+		Sha256::update(self, data);
+	}
+
+	#[inline]
+	fn finalize(self) -> [u8; 32] {
+		Sha256::finalize(self)
+	}
+}
+
+impl Hash for Sha512 {
+	const BLOCK_SIZE: usize = sha512::BLOCK_SIZE;
+
+	type Output = [u8; 64];
+
+	#[inline]
+	fn new() -> Self {
+		Sha512::new()
+	}
+
+	#[inline]
+	fn digest(data: &[u8]) -> [u8; 64] {
+		Sha512::digest(data)
+	}
+
+	#[inline]
+	fn update(&mut self, data: &[u8]) {
+		Sha512::update(self, data);
+	}
+
+	#[inline]
+	fn finalize(self) -> [u8; 64] {
+		Sha512::finalize(self)
+	}
+}
+
+pub type HmacSha256 = Hmac<Sha256>;
+pub type HmacSha512 = Hmac<Sha512>;
+
+#[derive(Clone)]
+pub struct Hmac<H: Hash> {
+	inner: H,
+	outer: H
+}
+
+impl<H: Hash> Hmac<H> {
+	pub fn new(key: &[u8]) -> Self {
+		let mut block = [0u8; MAX_BLOCK_SIZE];
+		let block = &mut block[..H::BLOCK_SIZE];
+
+		if key.len() > H::BLOCK_SIZE {
+			let mut digest = H::digest(key);
+			let digest = digest.as_mut();
+
+			block[..digest.len()].copy_from_slice(digest);
+			words::wipe(digest);
+		} else {
+			block[..key.len()].copy_from_slice(key);
+		}
+
+		let mut inner = H::new();
+		let mut outer = H::new();
+
+		for byte in block.iter_mut() {
+			*byte ^= IPAD;
+		}
+		inner.update(block);
+
+		for byte in block.iter_mut() {
+			*byte ^= IPAD ^ OPAD;
+		}
+		outer.update(block);
+
+		words::wipe(block);
+
+		Hmac {
+			inner,
+			outer
+		}
+	}
+
+	#[inline]
+	pub fn update(&mut self, data: &[u8]) {
+		self.inner.update(data);
+	}
+
+	pub fn finalize(self) -> H::Output {
+		let mut outer = self.outer;
+		let mut inner = self.inner.finalize();
+
+		outer.update(inner.as_ref());
+		words::wipe(inner.as_mut());
+
+		outer.finalize()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn hmac_sha256_rfc4231() {
+		// RFC 4231, test case 2
+		let expected: &[u8] = &[
+			0x5b,0xdc,0xc1,0x46,0xbf,0x60,0x75,0x4e,0x6a,0x04,0x24,0x26,0x08,
+			0x95,0x75,0xc7,0x5a,0x00,0x3f,0x08,0x9d,0x27,0x39,0x83,0x9d,0xec,
+			0x58,0xb9,0x64,0xec,0x38,0x43
+		];
+
+		let mut hmac = HmacSha256::new(b"Jefe");
+		hmac.update(b"what do ya want ");
+		hmac.update(b"for nothing?");
+
+		assert_eq!(&hmac.finalize()[..], expected);
+	}
+
+	#[test]
+	fn hmac_sha512_rfc4231() {
+		// RFC 4231, test case 2
+		let expected: &[u8] = &[
+			0x16,0x4b,0x7a,0x7b,0xfc,0xf8,0x19,0xe2,0xe3,0x95,0xfb,0xe7,0x3b,
+			0x56,0xe0,0xa3,0x87,0xbd,0x64,0x22,0x2e,0x83,0x1f,0xd6,0x10,0x27,
+			0x0c,0xd7,0xea,0x25,0x05,0x54,0x97,0x58,0xbf,0x75,0xc0,0x5a,0x99,
+			0x4a,0x6d,0x03,0x4f,0x65,0xf8,0xf0,0xe6,0xfd,0xca,0xea,0xb1,0xa3,
+			0x4d,0x4a,0x6b,0x4b,0x63,0x6e,0x07,0x0a,0x38,0xbc,0xe7,0x37
+		];
+
+		let mut hmac = HmacSha512::new(b"Jefe");
+		hmac.update(b"what do ya want ");
+		hmac.update(b"for nothing?");
+
+		assert_eq!(&hmac.finalize()[..], expected);
+	}
+
+	#[test]
+	fn hmac_sha512_long_key() {
+		// RFC 4231, test case 6
+		let expected: &[u8] = &[
+			0x80,0xb2,0x42,0x63,0xc7,0xc1,0xa3,0xeb,0xb7,0x14,0x93,0xc1,0xdd,
+			0x7b,0xe8,0xb4,0x9b,0x46,0xd1,0xf4,0x1b,0x4a,0xee,0xc1,0x12,0x1b,
+			0x01,0x37,0x83,0xf8,0xf3,0x52,0x6b,0x56,0xd0,0x37,0xe0,0x5f,0x25,
+			0x98,0xbd,0x0f,0xd2,0x21,0x5d,0x6a,0x1e,0x52,0x95,0xe6,0x4f,0x73,
+			0xf6,0x3f,0x0a,0xec,0x8b,0x91,0x5a,0x98,0x5d,0x78,0x65,0x98
+		];
+
+		let mut hmac = HmacSha512::new(&[0xaa; 131]);
+		hmac.update(b"Test Using Larger Than Block-Size Key - Hash Key First");
+
+		assert_eq!(&hmac.finalize()[..], expected);
+	}
+}
@@ -1,32 +1,64 @@
 #![no_std]
 
+#[cfg(feature = "std")]
+extern crate std;
+
+#[cfg(feature = "rand")]
+extern crate rand_core;
+
+mod error;
 pub mod big_num;
+pub mod scalar;
+pub mod field;
+mod words;
+mod modinv;
 mod naf;
-mod ec_point;
+pub mod ec_point;
 mod ec_point_g;
-mod ecj_point;
-
-pub use ec_point_g::ECPointG;
+#[cfg(test)]
+mod table_gen;
+pub mod ecj_point;
+mod public_key;
+mod secret_key;
+pub mod multi_mul;
+mod sha256;
+mod sha512;
+mod ripemd160;
+mod hmac;
+pub mod base58;
+mod rfc6979;
+mod ecdsa;
+mod tweak;
+pub mod bip32;
+
+pub use error::Error;
+pub use ec_point_g::{ECPointG, TablePoint, Window, Window2, Window4, Window8};
+#[cfg(static_table)]
+pub use ec_point_g::GlobalWindow;
+pub use ec_point::ECPoint;
+pub use ecj_point::ECJPoint;
 pub use big_num::BigNum;
+pub use scalar::Scalar;
+pub use field::FieldElement;
+pub use public_key::PublicKey;
+pub use secret_key::SecretKey;
+pub use multi_mul::{MultiMulScratch, multi_mul};
+pub use ecdsa::{Signature, sign, verify, recover};
+pub use tweak::{
+	secret_tweak_add, secret_tweak_mul, public_tweak_add, public_tweak_mul,
+	secret_negate, public_negate, public_combine
+};
 
 pub fn is_valid_secret(bytes: &[u8]) -> bool {
-	if bytes.len() != 32 {
-		return false;
-	}
-
-	let num = BigNum::from(bytes);
-
-	!num.is_overflow() && num != 0
+	SecretKey::parse(bytes).is_ok()
 }
 
-pub fn create_public_key(g: &ECPointG, secret: &[u8]) -> Option<[u8; 65]> {
-	let mut num = BigNum::from(secret);
-
-	if num.is_overflow() || num == 0 {
-		return None;
-	}
+pub fn create_public_key<W: Window>(g: &ECPointG<W>, secret: &[u8]) -> Result<[u8; 65], Error> {
+	Ok(SecretKey::parse(secret)?.public_key(g).serialize())
+}
 
-	Some(g.mul(&mut num).to_public_key())
+pub fn create_public_key_compressed<W: Window>(g: &ECPointG<W>, secret: &[u8]) -> Result<[u8; 33], Error> {
+	Ok(SecretKey::parse(secret)?.public_key(g).serialize_compressed())
 }
 
 #[cfg(test)]
@@ -79,4 +111,36 @@ mod tests {
 		assert_eq!(key.len(), expected.len());
 		assert_eq!(&key[..], expected);
 	}
+
+	#[test]
+	fn create_public_key_invalid_secret() {
+		let g = ECPointG::new();
+
+		assert_eq!(super::create_public_key(&g, &[0u8; 32]), Err(Error::InvalidSecretKey));
+		assert_eq!(super::create_public_key(&g, &[0xff; 32]), Err(Error::Overflow));
+		assert_eq!(super::create_public_key_compressed(&g, &[1u8; 33]), Err(Error::InvalidLength));
+		assert!(!is_valid_secret(&[0u8; 32]));
+		assert!(is_valid_secret(&[1u8; 32]));
+	}
+
+	#[test]
+	fn create_public_key_compressed() {
+		let secret: &[u8] = &[
+			0x4d,0x5d,0xb4,0x10,0x7d,0x23,0x7d,0xf6,0xa3,0xd5,0x8e,0xe5,0xf7,
+			0x0a,0xe6,0x3d,0x73,0xd7,0x65,0x8d,0x40,0x26,0xf2,0xee,0xfd,0x2f,
+			0x20,0x4c,0x81,0x68,0x2c,0xb7
+		];
+
+		let expected: &[u8] = &[
+			0x02,0x3f,0xa8,0xc0,0x8c,0x65,0xa8,0x3f,0x6b,0x4e,0xa3,0xe0,0x4e,
+			0x1c,0xc7,0x0c,0xbe,0x3c,0xd3,0x91,0x49,0x9e,0x3e,0x05,0xab,0x7d,
+			0xed,0xf2,0x8a,0xff,0x9a,0xfc,0x53
+		];
+
+		let g = ECPointG::new();
+
+		let key = super::create_public_key_compressed(&g, secret).unwrap();
+
+		assert_eq!(&key[..], expected);
+	}
 }
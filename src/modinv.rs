@@ -0,0 +1,381 @@
+//! Modular inversion with Bernstein-Yang "safegcd" divsteps, following
+//! the approach of libsecp256k1's `modinv32`. Numbers are represented as
+//! 9 signed limbs of 30 bits, which leaves room for the carries of the
+//! 2x2 transition matrices applied after every 30 divsteps.
+
+const M30: i32 = 0x3fff_ffff;
+
+/// A 256 bits odd modulus, in signed 30 bits limbs, together with its
+/// inverse modulo 2^30.
+pub struct ModInfo {
+	modulus: [i32; 9],
+	modulus_inv30: u32
+}
+
+pub const P: ModInfo = ModInfo {
+	modulus: [
+		0x3ffffc2f, 0x3ffffffb, 0x3fffffff, 0x3fffffff, 0x3fffffff,
+		0x3fffffff, 0x3fffffff, 0x3fffffff, 0xffff
+	],
+	modulus_inv30: 0x2ddacacf
+};
+
+pub const N: ModInfo = ModInfo {
+	modulus: [
+		0x10364141, 0x3f497a33, 0x348a03bb, 0x2bb739ab, 0x3ffffeba,
+		0x3fffffff, 0x3fffffff, 0x3fffffff, 0xffff
+	],
+	modulus_inv30: 0x2a774ec1
+};
+
+// [u v]
+// [q r], scaled by 2^30
+struct Trans2x2 {
+	u: i32,
+	v: i32,
+	q: i32,
+	r: i32
+}
+
+fn to_signed30(words: &[u32; 8]) -> [i32; 9] {
+	let mut limbs = [0i32; 9];
+
+	for (i, limb) in limbs.iter_mut().enumerate() {
+		let bit = i * 30;
+		let word = bit / 32;
+		let shift = bit % 32;
+
+		let mut v = words[word] >> shift;
+
+		if shift > 2 && word < 7 {
+			v |= words[word + 1] << (32 - shift);
+		}
+
+		*limb = v as i32 & M30;
+	}
+
+	limbs
+}
+
+fn from_signed30(limbs: &[i32; 9]) -> [u32; 8] {
+	let mut words = [0u32; 8];
+
+	for (i, &limb) in limbs.iter().enumerate() {
+		let bit = i * 30;
+		let word = bit / 32;
+		let shift = bit % 32;
+
+		words[word] |= (limb as u32) << shift;
+
+		if shift > 2 && word < 7 {
+			words[word + 1] |= (limb as u32) >> (32 - shift);
+		}
+	}
+
+	words
+}
+
+/// 30 divsteps of the "half delta" variant, without branching on `f` or
+/// `g`. Returns the new `zeta = -(delta + 1/2)`.
+fn divsteps_30(mut zeta: i32, f0: u32, g0: u32, t: &mut Trans2x2) -> i32 {
+	let (mut u, mut v, mut q, mut r) = (1u32, 0u32, 0u32, 1u32);
+	let (mut f, mut g) = (f0, g0);
+
+	for _ in 0..30 {
+		// c1 = zeta < 0 ? -1 : 0, c2 = g is odd ? -1 : 0
+		let mut c1 = (zeta >> 31) as u32;
+		let c2 = 0u32.wrapping_sub(g & 1);
+
+		// conditionally negate f, u, v if zeta < 0, and add to g, q, r if g is odd
+		let x = (f ^ c1).wrapping_sub(c1);
+		let y = (u ^ c1).wrapping_sub(c1);
+		let z = (v ^ c1).wrapping_sub(c1);
+
+		g = g.wrapping_add(x & c2);
+		q = q.wrapping_add(y & c2);
+		r = r.wrapping_add(z & c2);
+
+		// swap roles if zeta < 0 and g was odd
+		c1 &= c2;
+		zeta = (zeta ^ c1 as i32).wrapping_sub(1);
+
+		f = f.wrapping_add(g & c1);
+		u = u.wrapping_add(q & c1);
+		v = v.wrapping_add(r & c1);
+
+		g >>= 1;
+		u <<= 1;
+		v <<= 1;
+	}
+
+	t.u = u as i32;
+	t.v = v as i32;
+	t.q = q as i32;
+	t.r = r as i32;
+
+	zeta
+}
+
+/// 30 divsteps of the original variant, skipping runs of zero bits of `g`
+/// and eliminating up to 8 bits at once. Returns the new `eta = -delta`.
+fn divsteps_30_vartime(mut eta: i32, f0: u32, g0: u32, t: &mut Trans2x2) -> i32 {
+	let (mut u, mut v, mut q, mut r) = (1u32, 0u32, 0u32, 1u32);
+	let (mut f, mut g) = (f0, g0);
+	let mut i = 30;
+
+	loop {
+		// at most `i` zeroes are left to remove
+		let zeros = (g | (u32::MAX << i)).trailing_zeros();
+
+		g >>= zeros;
+		u <<= zeros;
+		v <<= zeros;
+		eta -= zeros as i32;
+		i -= zeros;
+
+		if i == 0 {
+			break;
+		}
+
+		if eta < 0 {
+			eta = -eta;
+
+			let tmp = f;
+			f = g;
+			g = tmp.wrapping_neg();
+
+			let tmp = u;
+			u = q;
+			q = tmp.wrapping_neg();
+
+			let tmp = v;
+			v = r;
+			r = tmp.wrapping_neg();
+		}
+
+		// cancel out as many bottom bits of g as eta and i allow
+		let limit = if eta + 1 > i as i32 { i } else { eta as u32 + 1 };
+		let m = (u32::MAX >> (32 - limit)) & 255;
+
+		// f^-1 mod 256 by Newton's iteration: f is odd so f * f = 1 (mod 8),
+		// and each step doubles the correct bits. Negated when used for `w`.
+		let mut inv = f;
+		inv = inv.wrapping_mul(2u32.wrapping_sub(f.wrapping_mul(inv)));
+		inv = inv.wrapping_mul(2u32.wrapping_sub(f.wrapping_mul(inv)));
+
+		let w = g.wrapping_mul(inv.wrapping_neg()) & m;
+
+		g = g.wrapping_add(f.wrapping_mul(w));
+		q = q.wrapping_add(u.wrapping_mul(w));
+		r = r.wrapping_add(v.wrapping_mul(w));
+	}
+
+	t.u = u as i32;
+	t.v = v as i32;
+	t.q = q as i32;
+	t.r = r as i32;
+
+	eta
+}
+
+/// `(d, e) = t * (d, e) / 2^30 (mod m)`, keeping both in (-2m, m).
+fn update_de(d: &mut [i32; 9], e: &mut [i32; 9], t: &Trans2x2, m: &ModInfo) {
+	let Trans2x2 { u, v, q, r } = *t;
+
+	let sd = d[8] >> 31;
+	let se = e[8] >> 31;
+
+	// add a multiple of the modulus to make the bottom 30 bits zero,
+	// starting with `m` if `d` or `e` is negative to keep them in range
+	let mut md = (u & sd).wrapping_add(v & se);
+	let mut me = (q & sd).wrapping_add(r & se);
+
+	let mut cd = u as i64 * d[0] as i64 + v as i64 * e[0] as i64;
+	let mut ce = q as i64 * d[0] as i64 + r as i64 * e[0] as i64;
+
+	md = md.wrapping_sub((m.modulus_inv30.wrapping_mul(cd as u32).wrapping_add(md as u32) as i32) & M30);
+	me = me.wrapping_sub((m.modulus_inv30.wrapping_mul(ce as u32).wrapping_add(me as u32) as i32) & M30);
+
+	cd += m.modulus[0] as i64 * md as i64;
+	ce += m.modulus[0] as i64 * me as i64;
+
+	cd >>= 30;
+	ce >>= 30;
+
+	for i in 1..9 {
+		cd += u as i64 * d[i] as i64 + v as i64 * e[i] as i64 + m.modulus[i] as i64 * md as i64;
+		ce += q as i64 * d[i] as i64 + r as i64 * e[i] as i64 + m.modulus[i] as i64 * me as i64;
+
+		d[i - 1] = cd as i32 & M30;
+		e[i - 1] = ce as i32 & M30;
+
+		cd >>= 30;
+		ce >>= 30;
+	}
+
+	d[8] = cd as i32;
+	e[8] = ce as i32;
+}
+
+/// `(f, g) = t * (f, g) / 2^30`
+fn update_fg(f: &mut [i32; 9], g: &mut [i32; 9], t: &Trans2x2) {
+	let Trans2x2 { u, v, q, r } = *t;
+
+	let mut cf = u as i64 * f[0] as i64 + v as i64 * g[0] as i64;
+	let mut cg = q as i64 * f[0] as i64 + r as i64 * g[0] as i64;
+
+	cf >>= 30;
+	cg >>= 30;
+
+	for i in 1..9 {
+		cf += u as i64 * f[i] as i64 + v as i64 * g[i] as i64;
+		cg += q as i64 * f[i] as i64 + r as i64 * g[i] as i64;
+
+		f[i - 1] = cf as i32 & M30;
+		g[i - 1] = cg as i32 & M30;
+
+		cf >>= 30;
+		cg >>= 30;
+	}
+
+	f[8] = cf as i32;
+	g[8] = cg as i32;
+}
+
+/// Bring `r` in (-2m, m) to [0, m), negating it first if `sign` is negative.
+fn normalize(r: &mut [i32; 9], sign: i32, m: &ModInfo) {
+	let cond_add = r[8] >> 31;
+
+	for (limb, ml) in r.iter_mut().zip(m.modulus.iter()) {
+		*limb = limb.wrapping_add(ml & cond_add);
+	}
+
+	let cond_negate = sign >> 31;
+
+	for limb in r.iter_mut() {
+		*limb = (*limb ^ cond_negate).wrapping_sub(cond_negate);
+	}
+
+	propagate(r);
+
+	let cond_add = r[8] >> 31;
+
+	for (limb, ml) in r.iter_mut().zip(m.modulus.iter()) {
+		*limb = limb.wrapping_add(ml & cond_add);
+	}
+
+	propagate(r);
+}
+
+#[inline]
+fn propagate(r: &mut [i32; 9]) {
+	for i in 0..8 {
+		r[i + 1] += r[i] >> 30;
+		r[i] &= M30;
+	}
+}
+
+/// Constant time inverse of `x` modulo `m`, zero maps to zero.
+pub fn inv(x: &[u32; 8], m: &ModInfo) -> [u32; 8] {
+	let mut d = [0i32; 9];
+	let mut e = [1i32, 0, 0, 0, 0, 0, 0, 0, 0];
+	let mut f = m.modulus;
+	let mut g = to_signed30(x);
+	let mut zeta = -1;
+	let mut t = Trans2x2 { u: 0, v: 0, q: 0, r: 0 };
+
+	// 20 * 30 = 600 divsteps, above the 590 bound for 256 bits inputs
+	for _ in 0..20 {
+		zeta = divsteps_30(zeta, f[0] as u32, g[0] as u32, &mut t);
+		update_de(&mut d, &mut e, &t, m);
+		update_fg(&mut f, &mut g, &t);
+	}
+
+	// f = +/-1 now, unless x was zero
+	normalize(&mut d, f[8], m);
+
+	from_signed30(&d)
+}
+
+/// Variable time inverse of `x` modulo `m`, only for public values.
+/// Zero maps to zero.
+pub fn inv_vartime(x: &[u32; 8], m: &ModInfo) -> [u32; 8] {
+	let mut d = [0i32; 9];
+	let mut e = [1i32, 0, 0, 0, 0, 0, 0, 0, 0];
+	let mut f = m.modulus;
+	let mut g = to_signed30(x);
+	let mut eta = -1;
+	let mut t = Trans2x2 { u: 0, v: 0, q: 0, r: 0 };
+
+	while g.iter().any(|&limb| limb != 0) {
+		eta = divsteps_30_vartime(eta, f[0] as u32, g[0] as u32, &mut t);
+		update_de(&mut d, &mut e, &t, m);
+		update_fg(&mut f, &mut g, &t);
+	}
+
+	normalize(&mut d, f[8], m);
+
+	from_signed30(&d)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use big_num::{self, BigNum};
+	use sha256::Sha256;
+	use words;
+
+	fn check(x: &[u32; 8], m: &ModInfo, bm: &BigNum) {
+		let expected = BigNum::from_be_bytes(&words::to_be_bytes(x)).invm(bm);
+		let expected = expected.to_be_bytes();
+
+		assert_eq!(words::to_be_bytes(&inv(x, m)), expected);
+		assert_eq!(words::to_be_bytes(&inv_vartime(x, m)), expected);
+	}
+
+	fn check_modulus(m: &ModInfo, bm: &BigNum) {
+		let mw = words::from_be_bytes(&bm.to_be_bytes());
+
+		// small values, powers of two and values just below the modulus
+		for i in 1..64u32 {
+			let mut below = mw;
+			below[0] -= i;
+
+			let mut pow = [0u32; 8];
+			pow[i as usize * 4 / 32] = 1 << (i * 4 % 32);
+
+			check(&[i, 0, 0, 0, 0, 0, 0, 0], m, bm);
+			check(&below, m, bm);
+			check(&pow, m, bm);
+		}
+
+		// top half and all ones in the low words
+		check(&[!0, !0, !0, !0, 0, 0, 0, 0], m, bm);
+		check(&[0, 0, 0, 0, !0, !0, !0, 0x7fffffff], m, bm);
+
+		// pseudorandom values below the modulus
+		let mut seed = [0u8; 32];
+
+		for _ in 0..256 {
+			seed = Sha256::digest(&seed);
+
+			let mut x = words::from_be_bytes(&seed);
+			x[7] &= 0x7fffffff;
+
+			check(&x, m, bm);
+		}
+
+		assert_eq!(inv(&[0; 8], m), [0; 8]);
+		assert_eq!(inv_vartime(&[0; 8], m), [0; 8]);
+	}
+
+	#[test]
+	fn modinv_matches_big_num_p() {
+		check_modulus(&P, big_num::P);
+	}
+
+	#[test]
+	fn modinv_matches_big_num_n() {
+		check_modulus(&N, big_num::N);
+	}
+}
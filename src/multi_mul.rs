@@ -0,0 +1,303 @@
+use core::iter::once;
+use core::mem::MaybeUninit;
+use core::ptr;
+use scalar::Scalar;
+use naf::NAF;
+use ec_point::{self, ECPoint, TABLE_LEN};
+use ecj_point::{self, ECJPoint};
+use words;
+
+/// Number of input points processed together by `strauss`, each one is
+/// split into two terms with the GLV endomorphism.
+pub const STRAUSS_BATCH: usize = 16;
+
+/// Number of input points processed together by `pippenger`
+pub const PIPPENGER_BATCH: usize = 128;
+
+/// Largest bucket window used by `pippenger`
+pub const PIPPENGER_MAX_WINDOW: usize = 7;
+
+/// Inputs of at least this many points are handled by `pippenger`
+pub const PIPPENGER_THRESHOLD: usize = 64;
+
+/// Bits covered by the signed digits of a GLV half, which is at most
+/// 128 bits long, with enough room for the offset of `recode_half`.
+const HALF_BITS: usize = 131;
+
+/// Working memory for `multi_mul`, kept out of the functions so that
+/// the caller decides where it lives and can reuse it between calls.
+/// At about 71 KB it's best built in place with `init`.
+pub struct MultiMulScratch {
+	nafs: [NAF; 2 * STRAUSS_BATCH],
+	tables: [[ECJPoint; TABLE_LEN]; 2 * STRAUSS_BATCH],
+	halves: [(ECPoint, [u8; 32]); 2 * PIPPENGER_BATCH],
+	buckets: [ECJPoint; 1 << (PIPPENGER_MAX_WINDOW - 1)]
+}
+
+impl Default for MultiMulScratch {
+	fn default() -> Self {
+		MultiMulScratch::new()
+	}
+}
+
+impl MultiMulScratch {
+	pub fn new() -> Self {
+		let mut slot = MaybeUninit::uninit();
+
+		MultiMulScratch::init(&mut slot);
+
+		// Safe since `init` has written every field
+		unsafe { slot.assume_init() }
+	}
+
+	/// Build the scratch directly in `slot`, which can be placed in
+	/// static or heap memory so that it never has to fit on the stack.
+	/// Returns a reference to the now initialized `slot`.
+	pub fn init(slot: &mut MaybeUninit<Self>) -> &mut Self {
+		let scratch = slot.as_mut_ptr();
+
+		// Safe since every element of every array is written once through
+		// a pointer into `slot`, without reading the uninitialized memory.
+		unsafe {
+			let nafs = ptr::addr_of_mut!((*scratch).nafs) as *mut NAF;
+
+			for i in 0..2 * STRAUSS_BATCH {
+				nafs.add(i).write(NAF::new());
+			}
+
+			let tables = ptr::addr_of_mut!((*scratch).tables) as *mut [ECJPoint; TABLE_LEN];
+
+			for i in 0..2 * STRAUSS_BATCH {
+				tables.add(i).write([ecj_point::INF; TABLE_LEN]);
+			}
+
+			let halves = ptr::addr_of_mut!((*scratch).halves) as *mut (ECPoint, [u8; 32]);
+
+			for i in 0..2 * PIPPENGER_BATCH {
+				halves.add(i).write((ec_point::INF, [0; 32]));
+			}
+
+			let buckets = ptr::addr_of_mut!((*scratch).buckets) as *mut ECJPoint;
+
+			for i in 0..1 << (PIPPENGER_MAX_WINDOW - 1) {
+				buckets.add(i).write(ecj_point::INF);
+			}
+
+			slot.assume_init_mut()
+		}
+	}
+}
+
+/// Computes the sum of `k * p` over all `terms`, picking the faster of
+/// `strauss` and `pippenger` for the number of terms. Scalars are
+/// treated as public, the running time depends on their values.
+pub fn multi_mul(terms: &[(ECPoint, Scalar)], scratch: &mut MultiMulScratch) -> ECJPoint {
+	if terms.len() < PIPPENGER_THRESHOLD {
+		strauss(terms, scratch)
+	} else {
+		pippenger(terms, scratch)
+	}
+}
+
+/// Interleaved wNAF multiplication, processing `STRAUSS_BATCH` points at
+/// a time. Every batch shares about 130 doublings, after the scalars are
+/// split in halves with the GLV endomorphism.
+pub fn strauss(terms: &[(ECPoint, Scalar)], scratch: &mut MultiMulScratch) -> ECJPoint {
+	let mut acc = ECJPoint::default();
+
+	for batch in terms.chunks(STRAUSS_BATCH) {
+		let nafs = &mut scratch.nafs[..2 * batch.len()];
+		let tables = &mut scratch.tables[..2 * batch.len()];
+
+		let halves = batch.iter().flat_map(|&(p, k)| {
+			let [a, b] = p.split_glv(&k);
+
+			once(a).chain(once(b))
+		});
+
+		for ((p, k), (naf, table)) in halves.zip(nafs.iter_mut().zip(tables.iter_mut())) {
+			ec_point::wnaf_term(&p, &k, naf, table);
+		}
+
+		acc += &ec_point::interleaved_wnaf(nafs, tables);
+	}
+
+	acc
+}
+
+/// Pippenger's bucket method, processing `PIPPENGER_BATCH` points at a
+/// time. The scalars are split in halves with the GLV endomorphism and
+/// recoded once into signed digits of `c` bits. For every window each
+/// point is then added to or subtracted from the bucket of the absolute
+/// value of its digit, and the buckets are summed with their weights
+/// using running sums. This costs about `131 / c * (n + 2^c)` additions
+/// for `n` halves.
+pub fn pippenger(terms: &[(ECPoint, Scalar)], scratch: &mut MultiMulScratch) -> ECJPoint {
+	let mut acc = ECJPoint::default();
+
+	for batch in terms.chunks(PIPPENGER_BATCH) {
+		acc += &pippenger_batch(batch, scratch);
+	}
+
+	acc
+}
+
+fn pippenger_batch(batch: &[(ECPoint, Scalar)], scratch: &mut MultiMulScratch) -> ECJPoint {
+	let c = pippenger_window(2 * batch.len());
+	let windows = HALF_BITS.div_ceil(c);
+	let half = 1 << (c - 1);
+
+	let halves = &mut scratch.halves[..2 * batch.len()];
+	let buckets = &mut scratch.buckets[..half];
+
+	let split = batch.iter().flat_map(|&(p, k)| {
+		let [a, b] = p.split_glv(&k);
+
+		once(a).chain(once(b))
+	});
+
+	for (entry, (p, k)) in halves.iter_mut().zip(split) {
+		*entry = (p, recode_half(&k, c, windows));
+	}
+
+	let mut acc = ECJPoint::default();
+
+	for window in (0..windows).rev() {
+		for _ in 0..c {
+			acc.double();
+		}
+
+		for bucket in buckets.iter_mut() {
+			*bucket = ECJPoint::default();
+		}
+
+		for (p, bytes) in halves.iter() {
+			// the digit plus 2^(c - 1)
+			let digit = words::read_bits(bytes, window * c, c);
+
+			if digit > half {
+				buckets[digit - half - 1] += p;
+			} else if digit < half {
+				buckets[half - digit - 1] -= p;
+			}
+		}
+
+		// sum of (i + 1) * buckets[i]
+		let mut running = ECJPoint::default();
+
+		for bucket in buckets.iter().rev() {
+			running += bucket;
+			acc += &running;
+		}
+	}
+
+	acc
+}
+
+/// Adds `2^(c - 1)` to every `c` bits window of `k`, so that each window
+/// of the result minus `2^(c - 1)` is a signed digit of `k`, in
+/// `[-2^(c - 1), 2^(c - 1))`, without carries between the windows.
+fn recode_half(k: &Scalar, c: usize, windows: usize) -> [u8; 32] {
+	let mut offset = [0u32; 8];
+
+	for window in 0..windows {
+		let bit = window * c + c - 1;
+
+		offset[bit / 32] |= 1 << (bit % 32);
+	}
+
+	let mut words = words::from_be_bytes(&k.to_bytes());
+	let mut carry = 0u64;
+
+	for (w, o) in words.iter_mut().zip(offset.iter()) {
+		carry += *w as u64 + *o as u64;
+		*w = carry as u32;
+		carry >>= 32;
+	}
+
+	words::to_be_bytes(&words)
+}
+
+/// The window minimizing the estimated number of additions for `n`
+/// halves, signed digits need at least 2 bits.
+fn pippenger_window(n: usize) -> usize {
+	(2..=PIPPENGER_MAX_WINDOW)
+		.min_by_key(|&c| HALF_BITS.div_ceil(c) * (n + (1 << c)))
+		.unwrap_or(2)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use scalar;
+	use ec_point::G;
+
+	fn make_terms(terms: &mut [(ECPoint, Scalar)]) {
+		let n = terms.len() as u32;
+		let mut p = ECJPoint::from(G);
+		let mut k = Scalar::from_bytes_reduced(&[0x3c; 32]);
+
+		for term in terms.iter_mut() {
+			*term = (ECPoint::from(p), k);
+
+			p += &G;
+			p.double();
+			k = k * &k + &Scalar::from(n);
+		}
+	}
+
+	fn naive(terms: &[(ECPoint, Scalar)]) -> ECJPoint {
+		terms
+			.iter()
+			.fold(ECJPoint::default(), |acc, &(p, k)| acc + &p.mul_glv(&k))
+	}
+
+	#[test]
+	fn multi_mul_matches_naive() {
+		let mut scratch = MultiMulScratch::new();
+		let mut terms = [(G, scalar::ZERO); 40];
+
+		for &n in [0, 1, 2, 17, 40].iter() {
+			let terms = &mut terms[..n];
+			make_terms(terms);
+
+			let expected = naive(terms);
+
+			assert_eq!(strauss(terms, &mut scratch), expected);
+			assert_eq!(pippenger(terms, &mut scratch), expected);
+			assert_eq!(multi_mul(terms, &mut scratch), expected);
+		}
+	}
+
+	#[test]
+	fn multi_mul_batches() {
+		let mut slot = MaybeUninit::uninit();
+		let scratch = MultiMulScratch::init(&mut slot);
+		let mut terms = [(G, scalar::ZERO); PIPPENGER_BATCH + 3];
+
+		make_terms(&mut terms);
+		terms[5].1 = -scalar::ONE;
+
+		let expected = naive(&terms);
+
+		assert_eq!(pippenger(&terms, scratch), expected);
+		assert_eq!(multi_mul(&terms, scratch), expected);
+		assert_eq!(strauss(&terms, scratch), expected);
+	}
+
+	#[test]
+	fn multi_mul_infinity() {
+		let mut scratch = MultiMulScratch::new();
+		let k = Scalar::from(7);
+		let terms = [(G, k), (ec_point::INF, k), (-G, k)];
+
+		assert_eq!(strauss(&terms, &mut scratch), ecj_point::INF);
+		assert_eq!(pippenger(&terms, &mut scratch), ecj_point::INF);
+	}
+
+	#[test]
+	fn pippenger_window_grows() {
+		assert!(pippenger_window(2) < pippenger_window(2 * PIPPENGER_BATCH));
+		assert!(pippenger_window(100_000) <= PIPPENGER_MAX_WINDOW);
+	}
+}
@@ -1,3 +1,6 @@
+use words;
+
+#[allow(clippy::upper_case_acronyms)]
 pub struct NAF {
 	data: [i8; 512],
 	len: usize
@@ -27,3 +30,10 @@ impl NAF {
 		&self.data[..self.len]
 	}
 }
+
+/// The digits reveal the scalar, so they are wiped on drop.
+impl Drop for NAF {
+	fn drop(&mut self) {
+		words::wipe(&mut self.data);
+	}
+}
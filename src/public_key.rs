@@ -0,0 +1,191 @@
+use field::FieldElement;
+use ec_point::ECPoint;
+use error::Error;
+
+/// A point on the curve usable as a public key, which can be serialized
+/// to and parsed from the SEC1 encodings.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PublicKey {
+	point: ECPoint
+}
+
+impl PublicKey {
+	/// Parse a 33 bytes compressed (`0x02`, `0x03`), 65 bytes uncompressed
+	/// (`0x04`) or hybrid (`0x06`, `0x07`) public key. Fails with
+	/// `InvalidLength` for any other length, and with `InvalidPublicKey` if
+	/// the prefix is wrong, a coordinate is not below `P` or the point
+	/// doesn't lie on the curve.
+	pub fn parse(bytes: &[u8]) -> Result<PublicKey, Error> {
+		let point = match bytes.len() {
+			33 => {
+				let odd = match bytes[0] {
+					0x02 => false,
+					0x03 => true,
+					_ => return Err(Error::InvalidPublicKey)
+				};
+
+				ECPoint::from_x(read_coordinate(&bytes[1..33])?, odd)
+					.ok_or(Error::InvalidPublicKey)?
+			},
+			65 => {
+				let x = read_coordinate(&bytes[1..33])?;
+				let y = read_coordinate(&bytes[33..65])?;
+
+				// hybrid encoding carries the parity of y in the prefix
+				match bytes[0] {
+					0x04 => {},
+					0x06 if !y.is_odd() => {},
+					0x07 if y.is_odd() => {},
+					_ => return Err(Error::InvalidPublicKey)
+				}
+
+				ECPoint::new(x, y)
+			},
+			_ => return Err(Error::InvalidLength)
+		};
+
+		PublicKey::from_point(point)
+	}
+
+	/// Fails with `InvalidPublicKey` if the point is infinity or not on
+	/// the curve.
+	pub fn from_point(point: ECPoint) -> Result<PublicKey, Error> {
+		if point.is_valid() {
+			Ok(PublicKey { point })
+		} else {
+			Err(Error::InvalidPublicKey)
+		}
+	}
+
+	/// For points known to be valid, such as non-zero multiples of `G`.
+	#[inline]
+	pub(crate) fn from_point_unchecked(point: ECPoint) -> PublicKey {
+		PublicKey { point }
+	}
+
+	/// 65 bytes uncompressed serialization
+	pub fn serialize(&self) -> [u8; 65] {
+		self.point.to_public_key()
+	}
+
+	/// 33 bytes compressed serialization
+	pub fn serialize_compressed(&self) -> [u8; 33] {
+		self.point.to_public_key_compressed()
+	}
+
+	#[inline]
+	pub(crate) fn point(&self) -> &ECPoint {
+		&self.point
+	}
+}
+
+impl From<PublicKey> for ECPoint {
+	#[inline]
+	fn from(public_key: PublicKey) -> ECPoint {
+		public_key.point
+	}
+}
+
+fn read_coordinate(buf: &[u8]) -> Result<FieldElement, Error> {
+	let mut bytes = [0u8; 32];
+	bytes.copy_from_slice(buf);
+
+	FieldElement::from_bytes(&bytes).map_err(|_| Error::InvalidPublicKey)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const G_UNCOMPRESSED: [u8; 65] = [
+		0x04,0x79,0xbe,0x66,0x7e,0xf9,0xdc,0xbb,0xac,0x55,0xa0,0x62,0x95,
+		0xce,0x87,0x0b,0x07,0x02,0x9b,0xfc,0xdb,0x2d,0xce,0x28,0xd9,0x59,
+		0xf2,0x81,0x5b,0x16,0xf8,0x17,0x98,0x48,0x3a,0xda,0x77,0x26,0xa3,
+		0xc4,0x65,0x5d,0xa4,0xfb,0xfc,0x0e,0x11,0x08,0xa8,0xfd,0x17,0xb4,
+		0x48,0xa6,0x85,0x54,0x19,0x9c,0x47,0xd0,0x8f,0xfb,0x10,0xd4,0xb8
+	];
+
+	#[test]
+	fn parse_public_key() {
+		let key = PublicKey::parse(&G_UNCOMPRESSED).unwrap();
+
+		assert_eq!(&key.serialize()[..], &G_UNCOMPRESSED[..]);
+
+		let compressed = key.serialize_compressed();
+
+		assert_eq!(compressed[0], 0x02);
+		assert_eq!(&compressed[1..], &G_UNCOMPRESSED[1..33]);
+		assert_eq!(PublicKey::parse(&compressed), Ok(key));
+
+		// -G has an odd y
+		let mut odd = compressed;
+		odd[0] = 0x03;
+
+		let neg = PublicKey::parse(&odd).unwrap();
+
+		assert_eq!(neg.point().y, -key.point().y);
+		assert_eq!(neg.serialize_compressed(), odd);
+	}
+
+	#[test]
+	fn parse_hybrid_public_key() {
+		let mut hybrid = G_UNCOMPRESSED;
+
+		hybrid[0] = 0x06;
+		assert_eq!(&PublicKey::parse(&hybrid).unwrap().serialize()[..], &G_UNCOMPRESSED[..]);
+
+		hybrid[0] = 0x07;
+		assert_eq!(PublicKey::parse(&hybrid), Err(Error::InvalidPublicKey));
+	}
+
+	#[test]
+	fn parse_invalid_public_key() {
+		assert_eq!(PublicKey::parse(&G_UNCOMPRESSED[..64]), Err(Error::InvalidLength));
+		assert_eq!(PublicKey::parse(&G_UNCOMPRESSED[..33]), Err(Error::InvalidPublicKey));
+
+		let mut off_curve = G_UNCOMPRESSED;
+		off_curve[64] ^= 1;
+
+		assert_eq!(PublicKey::parse(&off_curve), Err(Error::InvalidPublicKey));
+
+		// x = 5 has no point on the curve
+		let mut compressed = [0u8; 33];
+		compressed[0] = 0x02;
+		compressed[32] = 5;
+
+		assert_eq!(PublicKey::parse(&compressed), Err(Error::InvalidPublicKey));
+	}
+
+	#[test]
+	fn parse_public_key_overflowing_p() {
+		// x = 1 + P, which would reduce to the valid x = 1
+		let x: [u8; 32] = [
+			0xff,0xff,0xff,0xff,0xff,0xff,0xff,0xff,0xff,0xff,0xff,0xff,0xff,
+			0xff,0xff,0xff,0xff,0xff,0xff,0xff,0xff,0xff,0xff,0xff,0xff,0xff,
+			0xff,0xfe,0xff,0xff,0xfc,0x30
+		];
+		let y: [u8; 32] = [
+			0x42,0x18,0xf2,0x0a,0xe6,0xc6,0x46,0xb3,0x63,0xdb,0x68,0x60,0x58,
+			0x22,0xfb,0x14,0x26,0x4c,0xa8,0xd2,0x58,0x7f,0xdd,0x6f,0xbc,0x75,
+			0x0d,0x58,0x7e,0x76,0xa7,0xee
+		];
+
+		let mut compressed = [0u8; 33];
+		compressed[0] = 0x02;
+		compressed[32] = 0x01;
+
+		let key = PublicKey::parse(&compressed).unwrap();
+
+		assert_eq!(&key.serialize()[33..], &y[..]);
+
+		compressed[1..].copy_from_slice(&x);
+
+		assert_eq!(PublicKey::parse(&compressed), Err(Error::InvalidPublicKey));
+
+		let mut uncompressed = [0x04; 65];
+		uncompressed[1..33].copy_from_slice(&x);
+		uncompressed[33..].copy_from_slice(&y);
+
+		assert_eq!(PublicKey::parse(&uncompressed), Err(Error::InvalidPublicKey));
+	}
+}
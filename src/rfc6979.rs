@@ -0,0 +1,69 @@
+use hmac::HmacSha256;
+use words;
+
+/// HMAC-SHA256 DRBG as described in RFC 6979, section 3.2
+pub struct Rfc6979 {
+	k: [u8; 32],
+	v: [u8; 32],
+	retry: bool
+}
+
+impl Rfc6979 {
+	/// `secret` and `msg` are expected to be already reduced modulo `N`
+	/// (`int2octets` and `bits2octets` of the RFC respectively).
+	pub fn new(secret: &[u8; 32], msg: &[u8; 32]) -> Self {
+		let mut drbg = Rfc6979 {
+			k: [0x00; 32],
+			v: [0x01; 32],
+			retry: false
+		};
+
+		drbg.reseed(0x00, secret, msg);
+		drbg.reseed(0x01, secret, msg);
+
+		drbg
+	}
+
+	fn reseed(&mut self, sep: u8, secret: &[u8; 32], msg: &[u8; 32]) {
+		let mut hmac = HmacSha256::new(&self.k);
+		hmac.update(&self.v);
+		hmac.update(&[sep]);
+		hmac.update(secret);
+		hmac.update(msg);
+		self.k = hmac.finalize();
+
+		self.v = self.hmac_v();
+	}
+
+	#[inline]
+	fn hmac_v(&self) -> [u8; 32] {
+		let mut hmac = HmacSha256::new(&self.k);
+		hmac.update(&self.v);
+		hmac.finalize()
+	}
+
+	/// Produce the next nonce candidate. It is up to the caller to reject
+	/// candidates outside of `[1, N - 1]` and ask for another one.
+	pub fn next_nonce(&mut self) -> [u8; 32] {
+		if self.retry {
+			let mut hmac = HmacSha256::new(&self.k);
+			hmac.update(&self.v);
+			hmac.update(&[0x00]);
+			self.k = hmac.finalize();
+
+			self.v = self.hmac_v();
+		}
+
+		self.v = self.hmac_v();
+		self.retry = true;
+
+		self.v
+	}
+}
+
+impl Drop for Rfc6979 {
+	fn drop(&mut self) {
+		words::wipe(&mut self.k);
+		words::wipe(&mut self.v);
+	}
+}
@@ -0,0 +1,236 @@
+// message word selection for the left and right lines
+const R: [usize; 80] = [
+	0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15,
+	7, 4, 13, 1, 10, 6, 15, 3, 12, 0, 9, 5, 2, 14, 11, 8,
+	3, 10, 14, 4, 9, 15, 8, 1, 2, 7, 0, 6, 13, 11, 5, 12,
+	1, 9, 11, 10, 0, 8, 12, 4, 13, 3, 7, 15, 14, 5, 6, 2,
+	4, 0, 5, 9, 7, 12, 2, 10, 14, 1, 3, 8, 11, 6, 15, 13
+];
+
+const RR: [usize; 80] = [
+	5, 14, 7, 0, 9, 2, 11, 4, 13, 6, 15, 8, 1, 10, 3, 12,
+	6, 11, 3, 7, 0, 13, 5, 10, 14, 15, 8, 12, 4, 9, 1, 2,
+	15, 5, 1, 3, 7, 14, 6, 9, 11, 8, 12, 2, 10, 0, 4, 13,
+	8, 6, 4, 1, 3, 11, 15, 0, 5, 12, 2, 13, 9, 7, 10, 14,
+	12, 15, 10, 4, 1, 5, 8, 7, 6, 2, 13, 14, 0, 3, 9, 11
+];
+
+// rotation amounts for the left and right lines
+const S: [u32; 80] = [
+	11, 14, 15, 12, 5, 8, 7, 9, 11, 13, 14, 15, 6, 7, 9, 8,
+	7, 6, 8, 13, 11, 9, 7, 15, 7, 12, 15, 9, 11, 7, 13, 12,
+	11, 13, 6, 7, 14, 9, 13, 15, 14, 8, 13, 6, 5, 12, 7, 5,
+	11, 12, 14, 15, 14, 15, 9, 8, 9, 14, 5, 6, 8, 6, 5, 12,
+	9, 15, 5, 11, 6, 8, 13, 12, 5, 12, 13, 14, 11, 8, 5, 6
+];
+
+const SR: [u32; 80] = [
+	8, 9, 9, 11, 13, 15, 15, 5, 7, 7, 8, 11, 14, 14, 12, 6,
+	9, 13, 15, 7, 12, 8, 9, 11, 7, 7, 12, 7, 6, 15, 13, 11,
+	9, 7, 15, 11, 8, 6, 6, 14, 12, 13, 5, 14, 13, 13, 7, 5,
+	15, 5, 8, 11, 14, 14, 6, 14, 6, 9, 12, 9, 12, 5, 15, 8,
+	8, 5, 12, 9, 12, 5, 14, 6, 8, 13, 6, 5, 15, 13, 11, 11
+];
+
+const K: [u32; 5] = [0x00000000, 0x5a827999, 0x6ed9eba1, 0x8f1bbcdc, 0xa953fd4e];
+
+const KR: [u32; 5] = [0x50a28be6, 0x5c4dd124, 0x6d703ef3, 0x7a6d76e9, 0x00000000];
+
+const H: [u32; 5] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0];
+
+pub const BLOCK_SIZE: usize = 64;
+
+#[derive(Clone)]
+pub struct Ripemd160 {
+	state: [u32; 5],
+	buf: [u8; BLOCK_SIZE],
+	buf_len: usize,
+	len: u64
+}
+
+impl Default for Ripemd160 {
+	fn default() -> Self {
+		Ripemd160::new()
+	}
+}
+
+impl Ripemd160 {
+	pub fn new() -> Self {
+		Ripemd160 {
+			state: H,
+			buf: [0; BLOCK_SIZE],
+			buf_len: 0,
+			len: 0
+		}
+	}
+
+	pub fn digest(data: &[u8]) -> [u8; 20] {
+		let mut hasher = Ripemd160::new();
+		hasher.update(data);
+		hasher.finalize()
+	}
+
+	pub fn update(&mut self, mut data: &[u8]) {
+		self.len += data.len() as u64;
+
+		if self.buf_len != 0 {
+			let take = (BLOCK_SIZE - self.buf_len).min(data.len());
+
+			self.buf[self.buf_len..self.buf_len + take].copy_from_slice(&data[..take]);
+			self.buf_len += take;
+			data = &data[take..];
+
+			if self.buf_len < BLOCK_SIZE {
+				return;
+			}
+
+			let block = self.buf;
+			self.compress(&block);
+			self.buf_len = 0;
+		}
+
+		let mut blocks = data.chunks_exact(BLOCK_SIZE);
+
+		for block in &mut blocks {
+			self.compress(block);
+		}
+
+		let rest = blocks.remainder();
+
+		self.buf[..rest.len()].copy_from_slice(rest);
+		self.buf_len = rest.len();
+	}
+
+	pub fn finalize(mut self) -> [u8; 20] {
+		let bit_len = self.len.wrapping_mul(8);
+		let mut padding = [0u8; BLOCK_SIZE + 8];
+
+		padding[0] = 0x80;
+
+		// same padding as SHA-256, but with a little-endian length
+		let pad_len = if self.buf_len < 56 {
+			56 - self.buf_len
+		} else {
+			120 - self.buf_len
+		};
+
+		padding[pad_len..pad_len + 8].copy_from_slice(&bit_len.to_le_bytes());
+		self.update(&padding[..pad_len + 8]);
+
+		debug_assert!(self.buf_len == 0);
+
+		let mut out = [0u8; 20];
+
+		for (chunk, word) in out.chunks_exact_mut(4).zip(self.state.iter()) {
+			chunk.copy_from_slice(&word.to_le_bytes());
+		}
+
+		out
+	}
+
+	fn compress(&mut self, block: &[u8]) {
+		let mut x = [0u32; 16];
+
+		for (word, chunk) in x.iter_mut().zip(block.chunks_exact(4)) {
+			*word = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+		}
+
+		let [mut a, mut b, mut c, mut d, mut e] = self.state;
+		let [mut ar, mut br, mut cr, mut dr, mut er] = self.state;
+
+		for j in 0..80 {
+			let round = j / 16;
+
+			let t = a
+				.wrapping_add(f(round, b, c, d))
+				.wrapping_add(x[R[j]])
+				.wrapping_add(K[round])
+				.rotate_left(S[j])
+				.wrapping_add(e);
+
+			a = e;
+			e = d;
+			d = c.rotate_left(10);
+			c = b;
+			b = t;
+
+			// the right line uses the functions in reverse order
+			let t = ar
+				.wrapping_add(f(4 - round, br, cr, dr))
+				.wrapping_add(x[RR[j]])
+				.wrapping_add(KR[round])
+				.rotate_left(SR[j])
+				.wrapping_add(er);
+
+			ar = er;
+			er = dr;
+			dr = cr.rotate_left(10);
+			cr = br;
+			br = t;
+		}
+
+		let [h0, h1, h2, h3, h4] = self.state;
+
+		self.state = [
+			h1.wrapping_add(c).wrapping_add(dr),
+			h2.wrapping_add(d).wrapping_add(er),
+			h3.wrapping_add(e).wrapping_add(ar),
+			h4.wrapping_add(a).wrapping_add(br),
+			h0.wrapping_add(b).wrapping_add(cr)
+		];
+	}
+}
+
+#[inline]
+fn f(round: usize, x: u32, y: u32, z: u32) -> u32 {
+	match round {
+		0 => x ^ y ^ z,
+		1 => (x & y) | (!x & z),
+		2 => (x | !y) ^ z,
+		3 => (x & z) | (y & !z),
+		_ => x ^ (y | !z)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn ripemd160_empty() {
+		let expected: &[u8] = &[
+			0x9c,0x11,0x85,0xa5,0xc5,0xe9,0xfc,0x54,0x61,0x28,0x08,0x97,0x7e,
+			0xe8,0xf5,0x48,0xb2,0x25,0x8d,0x31
+		];
+
+		assert_eq!(&Ripemd160::digest(b"")[..], expected);
+	}
+
+	#[test]
+	fn ripemd160_multi_block() {
+		let short: &[u8] = &[
+			0x5d,0x06,0x89,0xef,0x49,0xd2,0xfa,0xe5,0x72,0xb8,0x81,0xb1,0x23,
+			0xa8,0x5f,0xfa,0x21,0x59,0x5f,0x36
+		];
+
+		assert_eq!(&Ripemd160::digest(b"message digest")[..], short);
+
+		let expected: &[u8] = &[
+			0x9b,0x75,0x2e,0x45,0x57,0x3d,0x4b,0x39,0xf4,0xdb,0xd3,0x32,0x3c,
+			0xab,0x82,0xbf,0x63,0x32,0x6b,0xfb
+		];
+
+		let data: &[u8] = b"1234567890123456789012345678901234567890\
+			1234567890123456789012345678901234567890";
+
+		assert_eq!(&Ripemd160::digest(data)[..], expected);
+
+		let mut hasher = Ripemd160::new();
+
+		for chunk in data.chunks(7) {
+			hasher.update(chunk);
+		}
+
+		assert_eq!(&hasher.finalize()[..], expected);
+	}
+}
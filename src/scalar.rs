@@ -0,0 +1,419 @@
+use core::ops::{Add, AddAssign, Sub, SubAssign, Mul, MulAssign, Neg};
+use core::convert::TryFrom;
+use error::Error;
+use big_num::BigNum;
+use words;
+use modinv;
+
+/// An integer modulo the group order `N`. The value is always kept
+/// reduced, and all arithmetic is performed on fixed 8 words without
+/// branching on the value.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Scalar {
+	words: [u32; 8]
+}
+
+// FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEBAAEDCE6AF48A03BBFD25E8CD0364141
+const N: [u32; 8] = [
+	0xd0364141, 0xbfd25e8c, 0xaf48a03b, 0xbaaedce6,
+	0xfffffffe, 0xffffffff, 0xffffffff, 0xffffffff
+];
+
+// 7FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF5D576E7357A4501DDFE92F46681B20A0
+const NH: [u32; 8] = [
+	0x681b20a0, 0xdfe92f46, 0x57a4501d, 0x5d576e73,
+	0xffffffff, 0xffffffff, 0xffffffff, 0x7fffffff
+];
+
+// 2^256 - N = 14551231950B75FC4402DA1732FC9BEBF
+const NC: [u32; 5] = [
+	0x2fc9bebf, 0x402da173, 0x50b75fc4, 0x45512319, 0x00000001
+];
+
+// Constants for splitting scalars with the GLV endomorphism, lattice
+// basis vectors (a1, b1), (a2, b2) with a + b * LAMBDA = 0 (mod N) and
+// g1 = round(2^384 * b2 / N), g2 = round(2^384 * -b1 / N)
+const MINUS_LAMBDA: [u32; 8] = [
+	0xb51283cf, 0xe0cfc810, 0x8ec739c2, 0xa880b9fc,
+	0x77ed9ba4, 0x5ad9e3fd, 0x3fa3cf1f, 0xac9c52b3
+];
+
+const MINUS_B1: [u32; 8] = [
+	0x0abfe4c3, 0x6f547fa9, 0x010e8828, 0xe4437ed6,
+	0x00000000, 0x00000000, 0x00000000, 0x00000000
+];
+
+const MINUS_B2: [u32; 8] = [
+	0x3db1562c, 0xd765cda8, 0x0774346d, 0x8a280ac5,
+	0xfffffffe, 0xffffffff, 0xffffffff, 0xffffffff
+];
+
+const G1: [u32; 8] = [
+	0x45dbb031, 0xe893209a, 0x71e8ca7f, 0x3daa8a14,
+	0x9284eb15, 0xe86c90e4, 0xa7d46bcd, 0x3086d221
+];
+
+const G2: [u32; 8] = [
+	0x8ac47f71, 0x1571b4ae, 0x9df506c6, 0x221208ac,
+	0x0abfe4c4, 0x6f547fa9, 0x010e8828, 0xe4437ed6
+];
+
+pub const ZERO: Scalar = Scalar { words: [0; 8] };
+
+pub const ONE: Scalar = Scalar { words: [1, 0, 0, 0, 0, 0, 0, 0] };
+
+/// A cube root of unity modulo `N`, multiplying a point by it is the
+/// same as multiplying its x coordinate by `field::BETA`.
+pub const LAMBDA: Scalar = Scalar {
+	words: [
+		0x1b23bd72, 0xdf02967c, 0x20816678, 0x122e22ea,
+		0x8812645a, 0xa5261c02, 0xc05c30e0, 0x5363ad4c
+	]
+};
+
+impl Scalar {
+	/// Reads a big-endian 32 bytes integer, fails with `Overflow` if
+	/// it's not below `N`.
+	pub fn from_bytes(bytes: &[u8; 32]) -> Result<Scalar, Error> {
+		let (scalar, overflow) = Scalar::from_bytes_overflow(bytes);
+
+		if overflow {
+			Err(Error::Overflow)
+		} else {
+			Ok(scalar)
+		}
+	}
+
+	/// Reads a big-endian 32 bytes integer, reducing it modulo `N`.
+	pub fn from_bytes_reduced(bytes: &[u8; 32]) -> Scalar {
+		Scalar::from_bytes_overflow(bytes).0
+	}
+
+	fn from_bytes_overflow(bytes: &[u8; 32]) -> (Scalar, bool) {
+		let mut words = words::from_be_bytes(bytes);
+
+		// 32 bytes are below 2N, a single conditional subtraction will do
+		let overflow = words::reduce(&mut words, 0, &NC);
+
+		(Scalar { words }, overflow == 1)
+	}
+
+	/// Reads a little-endian 32 bytes integer, fails with `Overflow` if
+	/// it's not below `N`.
+	pub fn from_le_bytes(bytes: &[u8; 32]) -> Result<Scalar, Error> {
+		let mut be = *bytes;
+		be.reverse();
+
+		Scalar::from_bytes(&be)
+	}
+
+	/// Big-endian bytes
+	#[inline]
+	pub fn to_bytes(self) -> [u8; 32] {
+		words::to_be_bytes(&self.words)
+	}
+
+	#[inline]
+	pub fn to_le_bytes(self) -> [u8; 32] {
+		words::to_le_bytes(&self.words)
+	}
+
+	/// Copy `src` into `self` if `flag` is set, without branching on it.
+	#[inline]
+	pub fn cmov(&mut self, src: &Scalar, flag: bool) {
+		words::select(&mut self.words, &src.words, flag as u32);
+	}
+
+	/// Set to zero with volatile writes, for scalars holding secrets.
+	pub fn wipe(&mut self) {
+		words::wipe(&mut self.words);
+	}
+
+	#[inline]
+	pub fn is_zero(&self) -> bool {
+		words::is_zero(&self.words)
+	}
+
+	/// Whether the value is above `N / 2`
+	pub fn is_high(&self) -> bool {
+		// NH - self underflows iff self > NH
+		let mut borrow = 0i64;
+
+		for (nh, w) in NH.iter().zip(self.words.iter()) {
+			borrow = (*nh as i64 - *w as i64 + borrow) >> 32;
+		}
+
+		borrow != 0
+	}
+
+	#[inline]
+	pub fn is_even(&self) -> bool {
+		self.words[0] & 1 == 0
+	}
+
+	/// Splits the scalar into `(k1, k2)` such that `self = k1 + k2 * LAMBDA`,
+	/// where `k1` and `k2`, or their negations, are at most 128 bits long.
+	pub fn split_lambda(&self) -> (Scalar, Scalar) {
+		let c1 = self.mul_shift_384(&G1) * &Scalar { words: MINUS_B1 };
+		let c2 = self.mul_shift_384(&G2) * &Scalar { words: MINUS_B2 };
+
+		let k2 = c1 + &c2;
+		let k1 = k2 * &Scalar { words: MINUS_LAMBDA } + self;
+
+		(k1, k2)
+	}
+
+	/// `round(self * g / 2^384)`, which is below 2^128 for the constants above
+	fn mul_shift_384(&self, g: &[u32; 8]) -> Scalar {
+		let l = words::mul_wide(&self.words, g);
+
+		let mut words = [0u32; 8];
+		words[..4].copy_from_slice(&l[12..]);
+
+		let mut carry = (l[11] >> 31) as u64;
+
+		for word in words.iter_mut() {
+			carry += *word as u64;
+			*word = carry as u32;
+			carry >>= 32;
+		}
+
+		Scalar { words }
+	}
+
+	/// Multiplicative inverse in constant time, zero maps to zero.
+	pub fn inv(&self) -> Scalar {
+		Scalar {
+			words: modinv::inv(&self.words, &modinv::N)
+		}
+	}
+
+	/// Multiplicative inverse, only to be used with public values.
+	pub fn inv_vartime(&self) -> Scalar {
+		Scalar {
+			words: modinv::inv_vartime(&self.words, &modinv::N)
+		}
+	}
+}
+
+impl From<u32> for Scalar {
+	fn from(n: u32) -> Self {
+		Scalar {
+			words: [n, 0, 0, 0, 0, 0, 0, 0]
+		}
+	}
+}
+
+/// Reads a big-endian 32 bytes integer, failing if the slice is not
+/// exactly 32 bytes long or the number is not below `N`.
+impl<'a> TryFrom<&'a [u8]> for Scalar {
+	type Error = Error;
+
+	fn try_from(buf: &'a [u8]) -> Result<Self, Error> {
+		if buf.len() != 32 {
+			return Err(Error::InvalidLength);
+		}
+
+		let mut bytes = [0u8; 32];
+		bytes.copy_from_slice(buf);
+
+		Scalar::from_bytes(&bytes)
+	}
+}
+
+impl<'a> From<&'a Scalar> for BigNum {
+	fn from(scalar: &'a Scalar) -> Self {
+		BigNum::from_be_bytes(&scalar.to_bytes())
+	}
+}
+
+impl Add<&Scalar> for Scalar {
+	type Output = Scalar;
+
+	#[inline]
+	fn add(mut self, rhs: &Scalar) -> Scalar {
+		self.add_assign(rhs);
+		self
+	}
+}
+
+impl AddAssign<&Scalar> for Scalar {
+	#[inline]
+	fn add_assign(&mut self, rhs: &Scalar) {
+		words::add(&mut self.words, &rhs.words, &NC);
+	}
+}
+
+impl Sub<&Scalar> for Scalar {
+	type Output = Scalar;
+
+	#[inline]
+	fn sub(mut self, rhs: &Scalar) -> Scalar {
+		self.sub_assign(rhs);
+		self
+	}
+}
+
+impl SubAssign<&Scalar> for Scalar {
+	#[inline]
+	fn sub_assign(&mut self, rhs: &Scalar) {
+		self.add_assign(&-*rhs);
+	}
+}
+
+impl Mul<&Scalar> for Scalar {
+	type Output = Scalar;
+
+	#[inline]
+	fn mul(mut self, rhs: &Scalar) -> Scalar {
+		self.mul_assign(rhs);
+		self
+	}
+}
+
+impl MulAssign<&Scalar> for Scalar {
+	#[inline]
+	fn mul_assign(&mut self, rhs: &Scalar) {
+		let wide = words::mul_wide(&self.words, &rhs.words);
+
+		self.words = words::reduce_wide(&wide, &NC);
+	}
+}
+
+impl Neg for Scalar {
+	type Output = Scalar;
+
+	#[inline]
+	fn neg(self) -> Scalar {
+		Scalar {
+			words: words::neg(&self.words, &N)
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use big_num;
+
+	#[test]
+	fn scalar_constants() {
+		let n_sub_1 = -ONE;
+		let nh = Scalar { words: NH };
+
+		assert_eq!(BigNum::from(&n_sub_1) + &BigNum::from(1), *big_num::N);
+		assert_eq!(BigNum::from(&nh), *big_num::NH);
+		assert_eq!(n_sub_1 + &ONE, ZERO);
+	}
+
+	#[test]
+	fn scalar_from_bytes() {
+		let n: [u8; 32] = [
+			0xFF,0xFF,0xFF,0xFF,0xFF,0xFF,0xFF,0xFF,0xFF,0xFF,0xFF,0xFF,0xFF,
+			0xFF,0xFF,0xFE,0xBA,0xAE,0xDC,0xE6,0xAF,0x48,0xA0,0x3B,0xBF,0xD2,
+			0x5E,0x8C,0xD0,0x36,0x41,0x41
+		];
+
+		let mut n_add_5 = n;
+		n_add_5[31] += 5;
+
+		assert_eq!(Scalar::from_bytes(&n), Err(Error::Overflow));
+		assert_eq!(Scalar::from_bytes_reduced(&n), ZERO);
+		assert_eq!(Scalar::from_bytes_reduced(&n_add_5), Scalar::from(5));
+
+		let mut n_sub_1 = n;
+		n_sub_1[31] -= 1;
+
+		assert_eq!(Scalar::from_bytes(&n_sub_1).unwrap().to_bytes(), n_sub_1);
+		assert_eq!(Scalar::try_from(&n_sub_1[..]), Ok(-ONE));
+		assert_eq!(Scalar::try_from(&n[..]), Err(Error::Overflow));
+		assert_eq!(Scalar::try_from(&n[..31]), Err(Error::InvalidLength));
+
+		let mut le = n_sub_1;
+		le.reverse();
+
+		assert_eq!(Scalar::from_le_bytes(&le), Ok(-ONE));
+		assert_eq!((-ONE).to_le_bytes(), le);
+	}
+
+	#[test]
+	fn scalar_add_sub() {
+		let a = -Scalar::from(5);
+		let b = Scalar::from(7);
+
+		assert_eq!(a + &b, Scalar::from(2));
+		assert_eq!(b - &Scalar::from(9), -Scalar::from(2));
+		assert_eq!(a - &a, ZERO);
+		assert_eq!(-ZERO, ZERO);
+	}
+
+	#[test]
+	fn scalar_mul() {
+		let a: [u8; 32] = [
+			0x4d,0x5d,0xb4,0x10,0x7d,0x23,0x7d,0xf6,0xa3,0xd5,0x8e,0xe5,0xf7,
+			0x0a,0xe6,0x3d,0x73,0xd7,0x65,0x8d,0x40,0x26,0xf2,0xee,0xfd,0x2f,
+			0x20,0x4c,0x81,0x68,0x2c,0xb7
+		];
+		let b: [u8; 32] = [
+			0x32,0x79,0xe8,0x0c,0xb3,0x93,0x5c,0x68,0xdc,0xf3,0x71,0xb9,0xee,
+			0x21,0x78,0x73,0x84,0xba,0xee,0x63,0xd6,0x49,0x0b,0x17,0x39,0x27,
+			0x10,0xc8,0x76,0xb1,0xa8,0x6b
+		];
+		let expected: [u8; 32] = [
+			0x2f,0xaa,0x72,0x13,0x52,0x7e,0x92,0x10,0xf1,0x2c,0xf8,0xfc,0x04,
+			0xe5,0x3f,0x31,0x35,0xe7,0xfa,0xb8,0xee,0xe1,0xa7,0x80,0x6a,0x02,
+			0xb2,0xb3,0x32,0x87,0x0a,0xdc
+		];
+
+		let a = Scalar::from_bytes(&a).unwrap();
+		let b = Scalar::from_bytes(&b).unwrap();
+
+		assert_eq!((a * &b).to_bytes(), expected);
+
+		// (N - 1)^2 = 1
+		assert_eq!(-ONE * &-ONE, ONE);
+	}
+
+	#[test]
+	fn scalar_inv() {
+		let a = Scalar::from(0x1337);
+
+		assert_eq!(a.inv() * &a, ONE);
+		assert_eq!(a.inv_vartime(), a.inv());
+		assert_eq!((-ONE).inv(), -ONE);
+		assert_eq!((-ONE).inv_vartime(), -ONE);
+		assert_eq!(ZERO.inv(), ZERO);
+		assert_eq!(ZERO.inv_vartime(), ZERO);
+	}
+
+	#[test]
+	fn scalar_split_lambda() {
+		// LAMBDA^3 = 1
+		assert_eq!(LAMBDA * &LAMBDA * &LAMBDA, ONE);
+
+		let mut k = Scalar::from_bytes_reduced(&[0xc3; 32]);
+
+		for _ in 0..16 {
+			let (k1, k2) = k.split_lambda();
+
+			assert_eq!(k1 + &(k2 * &LAMBDA), k);
+
+			for half in [k1, k2].iter() {
+				let half = if half.is_high() { -*half } else { *half };
+
+				assert_eq!(half.words[4..], [0; 4]);
+			}
+
+			k = k * &k + &ONE;
+		}
+	}
+
+	#[test]
+	fn scalar_is_high() {
+		let nh = Scalar { words: NH };
+
+		assert!(!nh.is_high());
+		assert!((nh + &ONE).is_high());
+		assert!(!ZERO.is_high());
+	}
+}
@@ -0,0 +1,196 @@
+use core::fmt;
+use core::convert::TryFrom;
+use error::Error;
+use scalar::Scalar;
+use ec_point_g::{ECPointG, Window};
+use public_key::PublicKey;
+#[cfg(feature = "rand")]
+use rand_core::{RngCore, CryptoRng};
+#[cfg(feature = "rand")]
+use words;
+
+/// A valid secret key: 32 bytes, not zero and below `N`. It's not `Copy`
+/// so that it isn't duplicated implicitly, its `Debug` output doesn't
+/// show the key, and it's wiped from memory when dropped.
+#[derive(Clone)]
+pub struct SecretKey {
+	scalar: Scalar
+}
+
+impl SecretKey {
+	/// Fails with `InvalidLength` if `bytes` is not 32 bytes long, with
+	/// `Overflow` if it's not below `N` and with `InvalidSecretKey` if
+	/// it's zero.
+	pub fn parse(bytes: &[u8]) -> Result<SecretKey, Error> {
+		SecretKey::from_scalar(Scalar::try_from(bytes)?)
+	}
+
+	pub fn from_bytes(bytes: &[u8; 32]) -> Result<SecretKey, Error> {
+		SecretKey::from_scalar(Scalar::from_bytes(bytes)?)
+	}
+
+	/// Fails with `InvalidSecretKey` if `scalar` is zero.
+	pub fn from_scalar(scalar: Scalar) -> Result<SecretKey, Error> {
+		if scalar.is_zero() {
+			return Err(Error::InvalidSecretKey);
+		}
+
+		Ok(SecretKey { scalar })
+	}
+
+	/// A uniformly random secret key. Candidates that are zero or not
+	/// below `N` are drawn again instead of being reduced, which would
+	/// bias the result. That happens with a probability of about 2^-128.
+	#[cfg(feature = "rand")]
+	pub fn generate<R: RngCore + CryptoRng>(rng: &mut R) -> SecretKey {
+		let mut bytes = [0u8; 32];
+
+		loop {
+			rng.fill_bytes(&mut bytes);
+
+			let res = SecretKey::from_bytes(&bytes);
+
+			words::wipe(&mut bytes);
+
+			if let Ok(key) = res {
+				return key;
+			}
+		}
+	}
+
+	/// `generate` with the operating system's RNG. Panics if it fails,
+	/// see `rand_core::OsRng`.
+	#[cfg(feature = "getrandom")]
+	pub fn generate_os() -> SecretKey {
+		SecretKey::generate(&mut rand_core::OsRng)
+	}
+
+	/// Big-endian bytes. The copy is not wiped, it's up to the caller.
+	pub fn to_bytes(&self) -> [u8; 32] {
+		self.scalar.to_bytes()
+	}
+
+	pub fn public_key<W: Window>(&self, g: &ECPointG<W>) -> PublicKey {
+		PublicKey::from_point_unchecked(g.mul(&self.scalar))
+	}
+
+	#[inline]
+	pub(crate) fn scalar(&self) -> &Scalar {
+		&self.scalar
+	}
+}
+
+impl<'a> TryFrom<&'a [u8]> for SecretKey {
+	type Error = Error;
+
+	fn try_from(bytes: &'a [u8]) -> Result<Self, Error> {
+		SecretKey::parse(bytes)
+	}
+}
+
+impl Drop for SecretKey {
+	fn drop(&mut self) {
+		self.scalar.wipe();
+	}
+}
+
+impl fmt::Debug for SecretKey {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str("SecretKey(..)")
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	extern crate std;
+
+	use super::*;
+	use core::mem::ManuallyDrop;
+	use self::std::format;
+	use ec_point::{self, ECPoint};
+
+	#[test]
+	fn parse_secret_key() {
+		let mut bytes = [0u8; 32];
+
+		assert_eq!(SecretKey::parse(&bytes).unwrap_err(), Error::InvalidSecretKey);
+		assert_eq!(SecretKey::parse(&[0xff; 32]).unwrap_err(), Error::Overflow);
+		assert_eq!(SecretKey::parse(&bytes[1..]).unwrap_err(), Error::InvalidLength);
+
+		bytes[31] = 1;
+
+		let key = SecretKey::from_bytes(&bytes).unwrap();
+		let g = ECPointG::new();
+
+		assert_eq!(key.to_bytes(), bytes);
+		assert_eq!(ECPoint::from(key.public_key(&g)), ec_point::G);
+	}
+
+	#[test]
+	fn secret_key_debug() {
+		let key = SecretKey::parse(&[0x5a; 32]).unwrap();
+
+		assert_eq!(format!("{:?}", key), "SecretKey(..)");
+	}
+
+	#[test]
+	fn secret_key_wiped_on_drop() {
+		let mut key = ManuallyDrop::new(SecretKey::parse(&[0x5a; 32]).unwrap());
+
+		unsafe { ManuallyDrop::drop(&mut key) };
+
+		assert!(key.scalar.is_zero());
+	}
+
+	/// Replays a list of 32 bytes outputs, one for every call, cut to
+	/// the length requested
+	#[cfg(feature = "rand")]
+	struct Replay<'a>(&'a [[u8; 32]]);
+
+	#[cfg(feature = "rand")]
+	impl<'a> RngCore for Replay<'a> {
+		fn next_u32(&mut self) -> u32 {
+			rand_core::impls::next_u32_via_fill(self)
+		}
+
+		fn next_u64(&mut self) -> u64 {
+			rand_core::impls::next_u64_via_fill(self)
+		}
+
+		fn fill_bytes(&mut self, dest: &mut [u8]) {
+			dest.copy_from_slice(&self.0[0][..dest.len()]);
+			self.0 = &self.0[1..];
+		}
+
+		fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+			self.fill_bytes(dest);
+			Ok(())
+		}
+	}
+
+	#[cfg(feature = "rand")]
+	impl<'a> CryptoRng for Replay<'a> {}
+
+	#[cfg(feature = "rand")]
+	#[test]
+	fn generate_rejects_invalid() {
+		let mut valid = [0xff; 32];
+		valid[0] = 0x7f;
+
+		let outputs = [[0xff; 32], [0x00; 32], valid, [0x11; 32]];
+		let mut rng = Replay(&outputs);
+
+		assert_eq!(SecretKey::generate(&mut rng).to_bytes(), valid);
+		assert_eq!(rng.0.len(), 1);
+		assert_eq!(rng.next_u32(), 0x11111111);
+	}
+
+	#[cfg(feature = "getrandom")]
+	#[test]
+	fn generate_os() {
+		let a = SecretKey::generate_os();
+		let b = SecretKey::generate_os();
+
+		assert!(a.to_bytes() != b.to_bytes());
+	}
+}
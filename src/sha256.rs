@@ -0,0 +1,201 @@
+use words;
+
+const K: [u32; 64] = [
+	0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+	0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+	0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+	0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+	0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+	0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+	0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+	0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2
+];
+
+const H: [u32; 8] = [
+	0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19
+];
+
+pub const BLOCK_SIZE: usize = 64;
+
+#[derive(Clone)]
+pub struct Sha256 {
+	state: [u32; 8],
+	buf: [u8; BLOCK_SIZE],
+	buf_len: usize,
+	len: u64
+}
+
+impl Default for Sha256 {
+	fn default() -> Self {
+		Sha256::new()
+	}
+}
+
+impl Sha256 {
+	pub fn new() -> Self {
+		Sha256 {
+			state: H,
+			buf: [0; BLOCK_SIZE],
+			buf_len: 0,
+			len: 0
+		}
+	}
+
+	pub fn digest(data: &[u8]) -> [u8; 32] {
+		let mut hasher = Sha256::new();
+		hasher.update(data);
+		hasher.finalize()
+	}
+
+	pub fn update(&mut self, mut data: &[u8]) {
+		self.len += data.len() as u64;
+
+		if self.buf_len != 0 {
+			let take = (BLOCK_SIZE - self.buf_len).min(data.len());
+
+			self.buf[self.buf_len..self.buf_len + take].copy_from_slice(&data[..take]);
+			self.buf_len += take;
+			data = &data[take..];
+
+			if self.buf_len < BLOCK_SIZE {
+				return;
+			}
+
+			let mut block = self.buf;
+			self.compress(&block);
+			words::wipe(&mut block);
+			self.buf_len = 0;
+		}
+
+		let mut blocks = data.chunks_exact(BLOCK_SIZE);
+
+		for block in &mut blocks {
+			self.compress(block);
+		}
+
+		let rest = blocks.remainder();
+
+		self.buf[..rest.len()].copy_from_slice(rest);
+		self.buf_len = rest.len();
+	}
+
+	pub fn finalize(mut self) -> [u8; 32] {
+		let bit_len = self.len.wrapping_mul(8);
+		let mut padding = [0u8; BLOCK_SIZE + 8];
+
+		padding[0] = 0x80;
+
+		// pad to 56 bytes mod 64, leaving room for the length
+		let pad_len = if self.buf_len < 56 {
+			56 - self.buf_len
+		} else {
+			120 - self.buf_len
+		};
+
+		padding[pad_len..pad_len + 8].copy_from_slice(&bit_len.to_be_bytes());
+		self.update(&padding[..pad_len + 8]);
+
+		debug_assert!(self.buf_len == 0);
+
+		let mut out = [0u8; 32];
+
+		for (chunk, word) in out.chunks_exact_mut(4).zip(self.state.iter()) {
+			chunk.copy_from_slice(&word.to_be_bytes());
+		}
+
+		out
+	}
+
+	fn compress(&mut self, block: &[u8]) {
+		let mut w = [0u32; 64];
+
+		for (word, chunk) in w.iter_mut().zip(block.chunks_exact(4)) {
+			*word = u32::from_be_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+		}
+
+		for i in 16..64 {
+			let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+			let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+
+			w[i] = w[i - 16]
+				.wrapping_add(s0)
+				.wrapping_add(w[i - 7])
+				.wrapping_add(s1);
+		}
+
+		let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = self.state;
+
+		for i in 0..64 {
+			let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+			let ch = (e & f) ^ (!e & g);
+			let t1 = h
+				.wrapping_add(s1)
+				.wrapping_add(ch)
+				.wrapping_add(K[i])
+				.wrapping_add(w[i]);
+			let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+			let maj = (a & b) ^ (a & c) ^ (b & c);
+			let t2 = s0.wrapping_add(maj);
+
+			h = g;
+			g = f;
+			f = e;
+			e = d.wrapping_add(t1);
+			d = c;
+			c = b;
+			b = a;
+			a = t1.wrapping_add(t2);
+		}
+
+		for (state, val) in self.state.iter_mut().zip([a, b, c, d, e, f, g, h].iter()) {
+			*state = state.wrapping_add(*val);
+		}
+
+		words::wipe(&mut w);
+	}
+}
+
+// the state and buffer hold secrets when hashing keys, as HMAC does
+impl Drop for Sha256 {
+	fn drop(&mut self) {
+		words::wipe(&mut self.state);
+		words::wipe(&mut self.buf);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn sha256_empty() {
+		let expected: &[u8] = &[
+			0xe3,0xb0,0xc4,0x42,0x98,0xfc,0x1c,0x14,0x9a,0xfb,0xf4,0xc8,0x99,
+			0x6f,0xb9,0x24,0x27,0xae,0x41,0xe4,0x64,0x9b,0x93,0x4c,0xa4,0x95,
+			0x99,0x1b,0x78,0x52,0xb8,0x55
+		];
+
+		assert_eq!(&Sha256::digest(b"")[..], expected);
+	}
+
+	#[test]
+	fn sha256_multi_block() {
+		let expected: &[u8] = &[
+			0x24,0x8d,0x6a,0x61,0xd2,0x06,0x38,0xb8,0xe5,0xc0,0x26,0x93,0x0c,
+			0x3e,0x60,0x39,0xa3,0x3c,0xe4,0x59,0x64,0xff,0x21,0x67,0xf6,0xec,
+			0xed,0xd4,0x19,0xdb,0x06,0xc1
+		];
+
+		let data = b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq";
+
+		assert_eq!(&Sha256::digest(data)[..], expected);
+
+		let mut hasher = Sha256::new();
+
+		for chunk in data.chunks(7) {
+			hasher.update(chunk);
+		}
+
+		assert_eq!(&hasher.finalize()[..], expected);
+	}
+}
@@ -0,0 +1,222 @@
+use words;
+
+const K: [u64; 80] = [
+	0x428a2f98d728ae22, 0x7137449123ef65cd, 0xb5c0fbcfec4d3b2f, 0xe9b5dba58189dbbc,
+	0x3956c25bf348b538, 0x59f111f1b605d019, 0x923f82a4af194f9b, 0xab1c5ed5da6d8118,
+	0xd807aa98a3030242, 0x12835b0145706fbe, 0x243185be4ee4b28c, 0x550c7dc3d5ffb4e2,
+	0x72be5d74f27b896f, 0x80deb1fe3b1696b1, 0x9bdc06a725c71235, 0xc19bf174cf692694,
+	0xe49b69c19ef14ad2, 0xefbe4786384f25e3, 0x0fc19dc68b8cd5b5, 0x240ca1cc77ac9c65,
+	0x2de92c6f592b0275, 0x4a7484aa6ea6e483, 0x5cb0a9dcbd41fbd4, 0x76f988da831153b5,
+	0x983e5152ee66dfab, 0xa831c66d2db43210, 0xb00327c898fb213f, 0xbf597fc7beef0ee4,
+	0xc6e00bf33da88fc2, 0xd5a79147930aa725, 0x06ca6351e003826f, 0x142929670a0e6e70,
+	0x27b70a8546d22ffc, 0x2e1b21385c26c926, 0x4d2c6dfc5ac42aed, 0x53380d139d95b3df,
+	0x650a73548baf63de, 0x766a0abb3c77b2a8, 0x81c2c92e47edaee6, 0x92722c851482353b,
+	0xa2bfe8a14cf10364, 0xa81a664bbc423001, 0xc24b8b70d0f89791, 0xc76c51a30654be30,
+	0xd192e819d6ef5218, 0xd69906245565a910, 0xf40e35855771202a, 0x106aa07032bbd1b8,
+	0x19a4c116b8d2d0c8, 0x1e376c085141ab53, 0x2748774cdf8eeb99, 0x34b0bcb5e19b48a8,
+	0x391c0cb3c5c95a63, 0x4ed8aa4ae3418acb, 0x5b9cca4f7763e373, 0x682e6ff3d6b2b8a3,
+	0x748f82ee5defb2fc, 0x78a5636f43172f60, 0x84c87814a1f0ab72, 0x8cc702081a6439ec,
+	0x90befffa23631e28, 0xa4506cebde82bde9, 0xbef9a3f7b2c67915, 0xc67178f2e372532b,
+	0xca273eceea26619c, 0xd186b8c721c0c207, 0xeada7dd6cde0eb1e, 0xf57d4f7fee6ed178,
+	0x06f067aa72176fba, 0x0a637dc5a2c898a6, 0x113f9804bef90dae, 0x1b710b35131c471b,
+	0x28db77f523047d84, 0x32caab7b40c72493, 0x3c9ebe0a15c9bebc, 0x431d67c49c100d4c,
+	0x4cc5d4becb3e42b6, 0x597f299cfc657e2a, 0x5fcb6fab3ad6faec, 0x6c44198c4a475817
+];
+
+const H: [u64; 8] = [
+	0x6a09e667f3bcc908, 0xbb67ae8584caa73b, 0x3c6ef372fe94f82b, 0xa54ff53a5f1d36f1,
+	0x510e527fade682d1, 0x9b05688c2b3e6c1f, 0x1f83d9abfb41bd6b, 0x5be0cd19137e2179
+];
+
+pub const BLOCK_SIZE: usize = 128;
+
+#[derive(Clone)]
+pub struct Sha512 {
+	state: [u64; 8],
+	buf: [u8; BLOCK_SIZE],
+	buf_len: usize,
+	len: u128
+}
+
+impl Default for Sha512 {
+	fn default() -> Self {
+		Sha512::new()
+	}
+}
+
+impl Sha512 {
+	pub fn new() -> Self {
+		Sha512 {
+			state: H,
+			buf: [0; BLOCK_SIZE],
+			buf_len: 0,
+			len: 0
+		}
+	}
+
+	pub fn digest(data: &[u8]) -> [u8; 64] {
+		let mut hasher = Sha512::new();
+		hasher.update(data);
+		hasher.finalize()
+	}
+
+	pub fn update(&mut self, mut data: &[u8]) {
+		self.len += data.len() as u128;
+
+		if self.buf_len != 0 {
+			let take = (BLOCK_SIZE - self.buf_len).min(data.len());
+
+			self.buf[self.buf_len..self.buf_len + take].copy_from_slice(&data[..take]);
+			self.buf_len += take;
+			data = &data[take..];
+
+			if self.buf_len < BLOCK_SIZE {
+				return;
+			}
+
+			let mut block = self.buf;
+			self.compress(&block);
+			words::wipe(&mut block);
+			self.buf_len = 0;
+		}
+
+		let mut blocks = data.chunks_exact(BLOCK_SIZE);
+
+		for block in &mut blocks {
+			self.compress(block);
+		}
+
+		let rest = blocks.remainder();
+
+		self.buf[..rest.len()].copy_from_slice(rest);
+		self.buf_len = rest.len();
+	}
+
+	pub fn finalize(mut self) -> [u8; 64] {
+		let bit_len = self.len.wrapping_mul(8);
+		let mut padding = [0u8; BLOCK_SIZE + 16];
+
+		padding[0] = 0x80;
+
+		// pad to 112 bytes mod 128, leaving room for the length
+		let pad_len = if self.buf_len < 112 {
+			112 - self.buf_len
+		} else {
+			240 - self.buf_len
+		};
+
+		padding[pad_len..pad_len + 16].copy_from_slice(&bit_len.to_be_bytes());
+		self.update(&padding[..pad_len + 16]);
+
+		debug_assert!(self.buf_len == 0);
+
+		let mut out = [0u8; 64];
+
+		for (chunk, word) in out.chunks_exact_mut(8).zip(self.state.iter()) {
+			chunk.copy_from_slice(&word.to_be_bytes());
+		}
+
+		out
+	}
+
+	fn compress(&mut self, block: &[u8]) {
+		let mut w = [0u64; 80];
+
+		for (word, chunk) in w.iter_mut().zip(block.chunks_exact(8)) {
+			let mut bytes = [0u8; 8];
+			bytes.copy_from_slice(chunk);
+
+			*word = u64::from_be_bytes(bytes);
+		}
+
+		for i in 16..80 {
+			let s0 = w[i - 15].rotate_right(1) ^ w[i - 15].rotate_right(8) ^ (w[i - 15] >> 7);
+			let s1 = w[i - 2].rotate_right(19) ^ w[i - 2].rotate_right(61) ^ (w[i - 2] >> 6);
+
+			w[i] = w[i - 16]
+				.wrapping_add(s0)
+				.wrapping_add(w[i - 7])
+				.wrapping_add(s1);
+		}
+
+		let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = self.state;
+
+		for i in 0..80 {
+			let s1 = e.rotate_right(14) ^ e.rotate_right(18) ^ e.rotate_right(41);
+			let ch = (e & f) ^ (!e & g);
+			let t1 = h
+				.wrapping_add(s1)
+				.wrapping_add(ch)
+				.wrapping_add(K[i])
+				.wrapping_add(w[i]);
+			let s0 = a.rotate_right(28) ^ a.rotate_right(34) ^ a.rotate_right(39);
+			let maj = (a & b) ^ (a & c) ^ (b & c);
+			let t2 = s0.wrapping_add(maj);
+
+			h = g;
+			g = f;
+			f = e;
+			e = d.wrapping_add(t1);
+			d = c;
+			c = b;
+			b = a;
+			a = t1.wrapping_add(t2);
+		}
+
+		for (state, val) in self.state.iter_mut().zip([a, b, c, d, e, f, g, h].iter()) {
+			*state = state.wrapping_add(*val);
+		}
+
+		words::wipe(&mut w);
+	}
+}
+
+// the state and buffer hold secrets when hashing keys, as HMAC does
+impl Drop for Sha512 {
+	fn drop(&mut self) {
+		words::wipe(&mut self.state);
+		words::wipe(&mut self.buf);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn sha512_empty() {
+		let expected: &[u8] = &[
+			0xcf,0x83,0xe1,0x35,0x7e,0xef,0xb8,0xbd,0xf1,0x54,0x28,0x50,0xd6,
+			0x6d,0x80,0x07,0xd6,0x20,0xe4,0x05,0x0b,0x57,0x15,0xdc,0x83,0xf4,
+			0xa9,0x21,0xd3,0x6c,0xe9,0xce,0x47,0xd0,0xd1,0x3c,0x5d,0x85,0xf2,
+			0xb0,0xff,0x83,0x18,0xd2,0x87,0x7e,0xec,0x2f,0x63,0xb9,0x31,0xbd,
+			0x47,0x41,0x7a,0x81,0xa5,0x38,0x32,0x7a,0xf9,0x27,0xda,0x3e
+		];
+
+		assert_eq!(&Sha512::digest(b"")[..], expected);
+	}
+
+	#[test]
+	fn sha512_multi_block() {
+		let expected: &[u8] = &[
+			0x8e,0x95,0x9b,0x75,0xda,0xe3,0x13,0xda,0x8c,0xf4,0xf7,0x28,0x14,
+			0xfc,0x14,0x3f,0x8f,0x77,0x79,0xc6,0xeb,0x9f,0x7f,0xa1,0x72,0x99,
+			0xae,0xad,0xb6,0x88,0x90,0x18,0x50,0x1d,0x28,0x9e,0x49,0x00,0xf7,
+			0xe4,0x33,0x1b,0x99,0xde,0xc4,0xb5,0x43,0x3a,0xc7,0xd3,0x29,0xee,
+			0xb6,0xdd,0x26,0x54,0x5e,0x96,0xe5,0x5b,0x87,0x4b,0xe9,0x09
+		];
+
+		let data: &[u8] = b"abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmn\
+			hijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu";
+
+		assert_eq!(&Sha512::digest(data)[..], expected);
+
+		let mut hasher = Sha512::new();
+
+		for chunk in data.chunks(11) {
+			hasher.update(chunk);
+		}
+
+		assert_eq!(&hasher.finalize()[..], expected);
+	}
+}
@@ -0,0 +1,181 @@
+//! Generation of the `ECPointG` tables embedded by the `static-table`
+//! features. This is shared with the build script, so it only depends on
+//! `words` and uses plain affine arithmetic with Fermat inversions, which
+//! is slow but simple enough to not need anything else from the crate.
+
+use core::fmt::{self, Write};
+use words;
+
+/// Name, `Window::BITS` and `Window::SPACING` of the tables the build
+/// script can embed, checked against the `Window` impls by the tests.
+pub const WINDOWS: [(&str, usize, usize); 3] = [
+	("w2", 2, 8),
+	("w4", 4, 4),
+	("w8", 8, 1)
+];
+
+// FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEFFFFFC2F
+const P: [u32; 8] = [
+	0xfffffc2f, 0xfffffffe, 0xffffffff, 0xffffffff,
+	0xffffffff, 0xffffffff, 0xffffffff, 0xffffffff
+];
+
+// P - 2, exponent for the inverse
+const P_SUB_2: [u32; 8] = [
+	0xfffffc2d, 0xfffffffe, 0xffffffff, 0xffffffff,
+	0xffffffff, 0xffffffff, 0xffffffff, 0xffffffff
+];
+
+// 2^256 - P = 1000003D1
+const PC: [u32; 2] = [0x000003d1, 0x00000001];
+
+const GX: [u32; 8] = [
+	0x16f81798, 0x59f2815b, 0x2dce28d9, 0x029bfcdb,
+	0xce870b07, 0x55a06295, 0xf9dcbbac, 0x79be667e
+];
+
+const GY: [u32; 8] = [
+	0xfb10d4b8, 0x9c47d08f, 0xa6855419, 0xfd17b448,
+	0x0e1108a8, 0x5da4fbfc, 0x26a3c465, 0x483ada77
+];
+
+type Point = ([u32; 8], [u32; 8]);
+
+fn add(a: &[u32; 8], b: &[u32; 8]) -> [u32; 8] {
+	let mut r = *a;
+
+	words::add(&mut r, b, &PC);
+
+	r
+}
+
+fn sub(a: &[u32; 8], b: &[u32; 8]) -> [u32; 8] {
+	add(a, &words::neg(b, &P))
+}
+
+fn mul(a: &[u32; 8], b: &[u32; 8]) -> [u32; 8] {
+	words::reduce_wide(&words::mul_wide(a, b), &PC)
+}
+
+fn inv(a: &[u32; 8]) -> [u32; 8] {
+	let mut res = [1, 0, 0, 0, 0, 0, 0, 0];
+
+	for word in P_SUB_2.iter().rev() {
+		for bit in (0..32).rev() {
+			res = mul(&res, &res);
+
+			if (word >> bit) & 1 == 1 {
+				res = mul(&res, a);
+			}
+		}
+	}
+
+	res
+}
+
+/// `a + b` for points that aren't infinity and don't add up to it
+fn add_points(a: &Point, b: &Point) -> Point {
+	let s = if a.0 == b.0 {
+		// 3 * x^2 / (2 * y)
+		let xx = mul(&a.0, &a.0);
+
+		mul(&add(&add(&xx, &xx), &xx), &inv(&add(&a.1, &a.1)))
+	} else {
+		mul(&sub(&b.1, &a.1), &inv(&sub(&b.0, &a.0)))
+	};
+
+	let x = sub(&sub(&mul(&s, &s), &a.0), &b.0);
+	let y = sub(&mul(&s, &sub(&a.0, &x)), &a.1);
+
+	(x, y)
+}
+
+/// Calls `f` with the coordinates of every point of the table for a
+/// window of `bits` and `spacing`, in the order of `ECPointG`'s table.
+pub fn table_points<F: FnMut(&[u32; 8], &[u32; 8])>(bits: usize, spacing: usize, mut f: F) {
+	let rows = 256usize.div_ceil(bits * spacing);
+	let cols = 1 << (bits - 1);
+	let mut base = (GX, GY);
+
+	for _ in 0..rows {
+		let mut point = base;
+
+		f(&point.0, &point.1);
+
+		for _ in 1..cols {
+			point = add_points(&point, &base);
+			f(&point.0, &point.1);
+		}
+
+		for _ in 0..bits * spacing {
+			base = add_points(&base, &base);
+		}
+	}
+}
+
+/// Writes the table as an array expression of `TablePoint`s.
+pub fn write_table<O: Write>(out: &mut O, bits: usize, spacing: usize) -> fmt::Result {
+	let mut result = out.write_str("[\n");
+
+	table_points(bits, spacing, |x, y| {
+		result = result.and_then(|_| write_point(out, x, y));
+	});
+
+	result?;
+
+	out.write_str("]\n")
+}
+
+fn write_point<O: Write>(out: &mut O, x: &[u32; 8], y: &[u32; 8]) -> fmt::Result {
+	out.write_str("\tTablePoint {\n")?;
+
+	for &(name, words) in [("x", x), ("y", y)].iter() {
+		write!(out, "\t\t{}: [", name)?;
+
+		for (i, word) in words.iter().enumerate() {
+			let sep = if i == 0 { "" } else { ", " };
+
+			write!(out, "{}0x{:08x}", sep, word)?;
+		}
+
+		out.write_str("],\n")?;
+	}
+
+	out.write_str("\t},\n")
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	// counts the points written, and keeps the first one
+	struct Count {
+		points: usize,
+		first: [u8; 256],
+		len: usize
+	}
+
+	impl Write for Count {
+		fn write_str(&mut self, s: &str) -> fmt::Result {
+			self.points += s.matches("TablePoint").count();
+
+			let n = s.len().min(self.first.len() - self.len);
+			self.first[self.len..self.len + n].copy_from_slice(&s.as_bytes()[..n]);
+			self.len += n;
+
+			Ok(())
+		}
+	}
+
+	#[test]
+	fn write_table_source() {
+		let mut out = Count { points: 0, first: [0; 256], len: 0 };
+
+		write_table(&mut out, 2, 8).unwrap();
+
+		let expected = "[\n\tTablePoint {\n\t\tx: [0x16f81798, 0x59f2815b, ";
+
+		assert_eq!(out.points, 32);
+		assert_eq!(&out.first[..expected.len()], expected.as_bytes());
+	}
+}
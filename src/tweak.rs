@@ -0,0 +1,209 @@
+//! Arithmetic on secret and public keys, matching the `ec_*_tweak_*`,
+//! `ec_*_negate` and `ec_pubkey_combine` functions of libsecp256k1.
+//! Tweaks must be below `N`, and a tweak turning the key into zero or
+//! infinity is rejected, both with `Error::InvalidTweak`.
+
+use error::Error;
+use scalar::Scalar;
+use ecj_point::ECJPoint;
+use ec_point::ECPoint;
+use ec_point_g::{ECPointG, Window};
+use secret_key::SecretKey;
+use public_key::PublicKey;
+
+/// `secret + tweak (mod N)`
+pub fn secret_tweak_add(secret: &SecretKey, tweak: &[u8; 32]) -> Result<SecretKey, Error> {
+	let mut t = parse_tweak(tweak)?;
+	let res = SecretKey::from_scalar(*secret.scalar() + &t);
+
+	t.wipe();
+
+	res.map_err(|_| Error::InvalidTweak)
+}
+
+/// `secret * tweak (mod N)`
+pub fn secret_tweak_mul(secret: &SecretKey, tweak: &[u8; 32]) -> Result<SecretKey, Error> {
+	let mut t = parse_tweak(tweak)?;
+	let res = SecretKey::from_scalar(*secret.scalar() * &t);
+
+	t.wipe();
+
+	res.map_err(|_| Error::InvalidTweak)
+}
+
+/// `public_key + tweak * G`, the public counterpart of `secret_tweak_add`
+pub fn public_tweak_add<W: Window>(g: &ECPointG<W>, public_key: &PublicKey, tweak: &[u8; 32]) -> Result<PublicKey, Error> {
+	let mut t = parse_tweak(tweak)?;
+	let mut point = ECJPoint::from(g.mul(&t));
+
+	t.wipe();
+	point.mixed_add(public_key.point());
+
+	if point.inf() {
+		return Err(Error::InvalidTweak);
+	}
+
+	Ok(PublicKey::from_point_unchecked(ECPoint::from(point)))
+}
+
+/// `public_key * tweak`, the public counterpart of `secret_tweak_mul`.
+/// The tweak is treated as public, the running time depends on it.
+pub fn public_tweak_mul(public_key: &PublicKey, tweak: &[u8; 32]) -> Result<PublicKey, Error> {
+	let t = parse_tweak(tweak)?;
+
+	// a non-zero multiple of a point of prime order is never infinity
+	if t.is_zero() {
+		return Err(Error::InvalidTweak);
+	}
+
+	let point = public_key.point().mul_glv(&t);
+
+	Ok(PublicKey::from_point_unchecked(point.to_affine_vartime()))
+}
+
+/// `N - secret`
+pub fn secret_negate(secret: &SecretKey) -> SecretKey {
+	SecretKey::from_scalar(-*secret.scalar()).expect("N - k is not zero for 0 < k < N; qed")
+}
+
+/// The point with the same `x` and negated `y`
+pub fn public_negate(public_key: &PublicKey) -> PublicKey {
+	PublicKey::from_point_unchecked(-*public_key.point())
+}
+
+/// The sum of all `keys`, accumulated in Jacobian coordinates and only
+/// normalized once at the end. Fails with `InvalidPublicKey` if the sum
+/// is infinity, which includes an empty `keys`.
+pub fn public_combine(keys: &[PublicKey]) -> Result<PublicKey, Error> {
+	let mut sum = ECJPoint::default();
+
+	for key in keys {
+		sum.mixed_add(key.point());
+	}
+
+	if sum.inf() {
+		return Err(Error::InvalidPublicKey);
+	}
+
+	Ok(PublicKey::from_point_unchecked(sum.to_affine_vartime()))
+}
+
+fn parse_tweak(tweak: &[u8; 32]) -> Result<Scalar, Error> {
+	Scalar::from_bytes(tweak).map_err(|_| Error::InvalidTweak)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use scalar;
+
+	const SECRET: [u8; 32] = [
+		0x4d,0x5d,0xb4,0x10,0x7d,0x23,0x7d,0xf6,0xa3,0xd5,0x8e,0xe5,0xf7,
+		0x0a,0xe6,0x3d,0x73,0xd7,0x65,0x8d,0x40,0x26,0xf2,0xee,0xfd,0x2f,
+		0x20,0x4c,0x81,0x68,0x2c,0xb7
+	];
+
+	const TWEAK: [u8; 32] = [
+		0xa0,0xdc,0x65,0xff,0xca,0x79,0x98,0x73,0xcb,0xea,0x0a,0xc2,0x74,
+		0x01,0x5b,0x95,0x26,0x50,0x5d,0xaa,0xae,0xd3,0x85,0x15,0x54,0x25,
+		0xf7,0x33,0x77,0x04,0x88,0x3e
+	];
+
+	#[test]
+	fn tweak_add() {
+		let g = ECPointG::new();
+		let secret = SecretKey::from_bytes(&SECRET).unwrap();
+		let public_key = secret.public_key(&g);
+
+		let tweaked = secret_tweak_add(&secret, &TWEAK).unwrap();
+		let expected = Scalar::from_bytes(&SECRET).unwrap() + &Scalar::from_bytes(&TWEAK).unwrap();
+
+		assert_eq!(tweaked.to_bytes(), expected.to_bytes());
+		assert_eq!(public_tweak_add(&g, &public_key, &TWEAK), Ok(tweaked.public_key(&g)));
+
+		// adding zero is allowed
+		assert_eq!(public_tweak_add(&g, &public_key, &[0; 32]), Ok(public_key));
+	}
+
+	#[test]
+	fn tweak_mul() {
+		let g = ECPointG::new();
+		let secret = SecretKey::from_bytes(&SECRET).unwrap();
+		let public_key = secret.public_key(&g);
+
+		let tweaked = secret_tweak_mul(&secret, &TWEAK).unwrap();
+		let expected = Scalar::from_bytes(&SECRET).unwrap() * &Scalar::from_bytes(&TWEAK).unwrap();
+
+		assert_eq!(tweaked.to_bytes(), expected.to_bytes());
+		assert_eq!(public_tweak_mul(&public_key, &TWEAK), Ok(tweaked.public_key(&g)));
+	}
+
+	#[test]
+	fn invalid_tweak() {
+		let g = ECPointG::new();
+		let secret = SecretKey::from_bytes(&SECRET).unwrap();
+		let public_key = secret.public_key(&g);
+
+		// N
+		let n: [u8; 32] = [
+			0xff,0xff,0xff,0xff,0xff,0xff,0xff,0xff,0xff,0xff,0xff,0xff,0xff,
+			0xff,0xff,0xfe,0xba,0xae,0xdc,0xe6,0xaf,0x48,0xa0,0x3b,0xbf,0xd2,
+			0x5e,0x8c,0xd0,0x36,0x41,0x41
+		];
+
+		assert_eq!(secret_tweak_add(&secret, &n).unwrap_err(), Error::InvalidTweak);
+		assert_eq!(secret_tweak_mul(&secret, &n).unwrap_err(), Error::InvalidTweak);
+		assert_eq!(public_tweak_add(&g, &public_key, &n), Err(Error::InvalidTweak));
+		assert_eq!(public_tweak_mul(&public_key, &n), Err(Error::InvalidTweak));
+
+		assert_eq!(secret_tweak_mul(&secret, &[0; 32]).unwrap_err(), Error::InvalidTweak);
+		assert_eq!(public_tweak_mul(&public_key, &[0; 32]), Err(Error::InvalidTweak));
+
+		// N - secret cancels the key out
+		let neg = (scalar::ZERO - secret.scalar()).to_bytes();
+
+		assert_eq!(secret_tweak_add(&secret, &neg).unwrap_err(), Error::InvalidTweak);
+		assert_eq!(public_tweak_add(&g, &public_key, &neg), Err(Error::InvalidTweak));
+	}
+
+	#[test]
+	fn negate() {
+		let g = ECPointG::new();
+		let secret = SecretKey::from_bytes(&SECRET).unwrap();
+		let public_key = secret.public_key(&g);
+
+		let neg = secret_negate(&secret);
+
+		assert_eq!(*neg.scalar() + secret.scalar(), scalar::ZERO);
+		assert_eq!(neg.public_key(&g), public_negate(&public_key));
+		assert_eq!(public_negate(&public_negate(&public_key)), public_key);
+
+		let compressed = public_key.serialize_compressed();
+		let negated = public_negate(&public_key).serialize_compressed();
+
+		assert_eq!(compressed[0] ^ negated[0], 1);
+		assert_eq!(&compressed[1..], &negated[1..]);
+	}
+
+	#[test]
+	fn combine() {
+		let g = ECPointG::new();
+		let a = SecretKey::from_bytes(&SECRET).unwrap();
+		let b = SecretKey::from_bytes(&TWEAK).unwrap();
+		let keys = [a.public_key(&g), b.public_key(&g), a.public_key(&g)];
+
+		let ab = secret_tweak_add(&a, &TWEAK).unwrap();
+		let aba = secret_tweak_add(&ab, &SECRET).unwrap();
+
+		assert_eq!(public_combine(&keys[..1]), Ok(keys[0]));
+		assert_eq!(public_combine(&keys[..2]), Ok(ab.public_key(&g)));
+		assert_eq!(public_combine(&keys), Ok(aba.public_key(&g)));
+
+		let aa = secret_tweak_add(&a, &SECRET).unwrap();
+
+		assert_eq!(public_combine(&[keys[0], keys[0]]), Ok(aa.public_key(&g)));
+
+		assert_eq!(public_combine(&[]), Err(Error::InvalidPublicKey));
+		assert_eq!(public_combine(&[keys[0], public_negate(&keys[0])]), Err(Error::InvalidPublicKey));
+	}
+}
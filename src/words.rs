@@ -0,0 +1,213 @@
+//! Helpers for fixed 8 words (256 bits) arithmetic modulo `2^256 - c`
+//! with a small `c`, shared by `Scalar` and `FieldElement`. None of
+//! these branch on the values of the words.
+
+use core::ptr;
+use core::sync::atomic;
+
+/// All ones if `flag` is set, all zeroes otherwise
+#[inline]
+pub fn mask(flag: u32) -> u32 {
+	0u32.wrapping_sub(flag)
+}
+
+/// 1 if `a == b`, 0 otherwise, without branching
+#[inline]
+pub fn eq(a: u32, b: u32) -> u32 {
+	let x = a ^ b;
+
+	// x | -x has the top bit set iff x != 0
+	((x | x.wrapping_neg()) >> 31) ^ 1
+}
+
+/// Conditionally copy `src` into `dst` without branching on `flag`
+#[inline]
+pub fn select(dst: &mut [u32; 8], src: &[u32; 8], flag: u32) {
+	let m = mask(flag);
+
+	for (d, s) in dst.iter_mut().zip(src.iter()) {
+		*d = (*d & !m) | (s & m);
+	}
+}
+
+/// Overwrite `values` with zeroes through volatile writes, which the
+/// compiler can't elide even if the memory is never read again.
+#[inline(never)]
+pub fn wipe<T: Copy + Default>(values: &mut [T]) {
+	for value in values.iter_mut() {
+		unsafe { ptr::write_volatile(value, T::default()) };
+	}
+
+	atomic::compiler_fence(atomic::Ordering::SeqCst);
+}
+
+pub fn from_be_bytes(bytes: &[u8; 32]) -> [u32; 8] {
+	let mut words = [0u32; 8];
+
+	for (word, chunk) in words.iter_mut().rev().zip(bytes.chunks_exact(4)) {
+		*word = u32::from_be_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+	}
+
+	words
+}
+
+pub fn to_be_bytes(words: &[u32; 8]) -> [u8; 32] {
+	let mut bytes = [0u8; 32];
+
+	for (chunk, word) in bytes.chunks_exact_mut(4).zip(words.iter().rev()) {
+		chunk.copy_from_slice(&word.to_be_bytes());
+	}
+
+	bytes
+}
+
+pub fn from_le_bytes(bytes: &[u8; 32]) -> [u32; 8] {
+	let mut words = [0u32; 8];
+
+	for (word, chunk) in words.iter_mut().zip(bytes.chunks_exact(4)) {
+		*word = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+	}
+
+	words
+}
+
+pub fn to_le_bytes(words: &[u32; 8]) -> [u8; 32] {
+	let mut bytes = [0u8; 32];
+
+	for (chunk, word) in bytes.chunks_exact_mut(4).zip(words.iter()) {
+		chunk.copy_from_slice(&word.to_le_bytes());
+	}
+
+	bytes
+}
+
+/// `count` bits of a big-endian 256 bits number, starting at bit `offset`
+/// counted from the least significant one.
+pub fn read_bits(bytes: &[u8; 32], offset: usize, count: usize) -> usize {
+	let mut value = 0;
+
+	for bit in (offset..(offset + count).min(256)).rev() {
+		let byte = bytes[31 - bit / 8];
+
+		value = (value << 1) | ((byte >> (bit % 8)) & 1) as usize;
+	}
+
+	value
+}
+
+#[inline]
+pub fn is_zero(words: &[u32; 8]) -> bool {
+	words.iter().fold(0, |acc, w| acc | w) == 0
+}
+
+/// Full 512 bits product of two 256 bits numbers
+pub fn mul_wide(a: &[u32; 8], b: &[u32; 8]) -> [u32; 16] {
+	let mut l = [0u32; 16];
+
+	for (i, x) in a.iter().enumerate() {
+		let mut carry = 0u64;
+
+		for (j, y) in b.iter().enumerate() {
+			let t = l[i + j] as u64 + *x as u64 * *y as u64 + carry;
+			l[i + j] = t as u32;
+			carry = t >> 32;
+		}
+
+		l[i + 8] = carry as u32;
+	}
+
+	l
+}
+
+/// `out = lo + hi * c`, `out` must be long enough to hold the result.
+pub fn mul_add_c(lo: &[u32], hi: &[u32], c: &[u32], out: &mut [u32]) {
+	for w in out.iter_mut() {
+		*w = 0;
+	}
+
+	out[..lo.len()].copy_from_slice(lo);
+
+	for (i, &h) in hi.iter().enumerate() {
+		let mut carry = 0u64;
+
+		for (j, &cw) in c.iter().enumerate() {
+			let t = out[i + j] as u64 + h as u64 * cw as u64 + carry;
+			out[i + j] = t as u32;
+			carry = t >> 32;
+		}
+
+		for w in out[i + c.len()..].iter_mut() {
+			let t = *w as u64 + carry;
+			*w = t as u32;
+			carry = t >> 32;
+		}
+	}
+}
+
+/// Subtracts the modulus `2^256 - c` from `words` if there was a `carry`
+/// out of 2^256 or `words` is not below the modulus. Returns 1 if the
+/// subtraction happened.
+pub fn reduce(words: &mut [u32; 8], carry: u32, c: &[u32]) -> u32 {
+	// words + c overflows 2^256 iff words >= 2^256 - c
+	let mut reduced = [0u32; 8];
+	let mut acc = 0u64;
+
+	for (i, r) in reduced.iter_mut().enumerate() {
+		acc += words[i] as u64 + *c.get(i).unwrap_or(&0) as u64;
+		*r = acc as u32;
+		acc >>= 32;
+	}
+
+	let overflow = carry | acc as u32;
+
+	select(words, &reduced, overflow);
+
+	overflow
+}
+
+/// Reduces a 512 bits number modulo `2^256 - c`, by folding the high
+/// words multiplied by `c` into the low words. For `c` of up to 129 bits
+/// three rounds are enough to get below 2^257.
+pub fn reduce_wide(l: &[u32; 16], c: &[u32]) -> [u32; 8] {
+	let mut a = *l;
+	let mut b = [0u32; 16];
+
+	mul_add_c(&a[..8], &a[8..], c, &mut b);
+	mul_add_c(&b[..8], &b[8..], c, &mut a);
+	mul_add_c(&a[..8], &a[8..], c, &mut b);
+
+	let mut words = [0u32; 8];
+	words.copy_from_slice(&b[..8]);
+
+	reduce(&mut words, b[8], c);
+
+	words
+}
+
+/// Adds two reduced numbers modulo `2^256 - c`
+pub fn add(a: &mut [u32; 8], b: &[u32; 8], c: &[u32]) {
+	let mut carry = 0u64;
+
+	for (x, y) in a.iter_mut().zip(b.iter()) {
+		carry += *x as u64 + *y as u64;
+		*x = carry as u32;
+		carry >>= 32;
+	}
+
+	reduce(a, carry as u32, c);
+}
+
+/// `m - a` for a reduced non-zero `a`, zero stays zero
+pub fn neg(a: &[u32; 8], m: &[u32; 8]) -> [u32; 8] {
+	let nonzero = mask(!is_zero(a) as u32);
+	let mut words = [0u32; 8];
+	let mut borrow = 0i64;
+
+	for (i, w) in words.iter_mut().enumerate() {
+		let t = m[i] as i64 - a[i] as i64 + borrow;
+		*w = t as u32 & nonzero;
+		borrow = t >> 32;
+	}
+
+	words
+}